@@ -0,0 +1,25 @@
+#[macro_use]
+extern crate criterion;
+extern crate nixrs;
+
+use criterion::{Criterion, Throughput};
+use nixrs::context::EvalContext;
+use nixrs::lex::Lexer;
+use nixrs::symbol::Symbol;
+
+include!("support/large_fixture.rs");
+
+fn lex_large_file(c: &mut Criterion) {
+    // Generate and register the file once so only lexing is measured.
+    let ctx = EvalContext::new();
+    let file = ctx.register_file(Symbol::new("large.nix"), large_fixture());
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(file.source.len() as u64));
+    group.bench_function("large.nix", |b| {
+        b.iter(|| Lexer::from_file(&ctx, &file).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, lex_large_file);
+criterion_main!(benches);
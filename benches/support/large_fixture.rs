@@ -0,0 +1,38 @@
+/// A large synthetic Nix file for the lexer benchmark (`benches/lexer.rs`) and throughput test
+/// (`tests/lexer_throughput.rs`), which both `include!` this file. It's an attrset of 250 package
+/// definitions, together exercising strings with interpolation, indented strings, paths, numbers,
+/// comments, operators and keywords. Generating it keeps a 3000-line fixture out of the repo.
+fn large_fixture() -> String {
+    use std::cmp::max;
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    out.push_str("# Synthetic input for the lexer benchmark and throughput test.\n{\n");
+    for i in 0..250 {
+        write!(out, "  pkg{i} = {{
+    name = \"package-{i}-${{version}}\";
+    version = \"{major}.{minor}.{patch}\";
+    size = {size}; ratio = {ratio}.{tenths}5;
+    src = ./sources/pkg{i}.tar.gz; /* local source */
+    deps = [ pkg{dep1} pkg{dep2} ] ++ lib.optional (size > 100) pkgs.extra;
+    script = ''
+      echo \"building ${{name}}\" > $out
+      make -j''${{NIX_BUILD_CORES}}
+    '';
+    enable = if i{i} >= 3 && !disabled then true else false;
+  }};
+",
+            i = i,
+            major = i % 7,
+            minor = i % 13,
+            patch = i % 5,
+            size = i * 37 % 100_000,
+            ratio = i % 97,
+            tenths = i % 10,
+            dep1 = max(i - 1, 0),
+            dep2 = max(i - 2, 0),
+        ).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
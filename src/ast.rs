@@ -1,3 +1,4 @@
+use lex::TokenKind;
 use symbol::Symbol;
 
 #[derive(Debug)]
@@ -8,6 +9,100 @@ pub enum Expr {
     String(Symbol),
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Binary operators
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BinOp {
+    Concat,    // ++
+    Mult,      // *
+    Divide,    // /
+    Add,       // +
+    Subtract,  // -
+    Update,    // //
+    Less,      // <
+    LessEq,    // <=
+    Greater,   // >
+    GreaterEq, // >=
+    Equals,    // ==
+    NotEquals, // !=
+    And,       // &&
+    Or,        // ||
+    Implies,   // ->
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Assoc {
+    Left,
+    Right,
+
+    /// The operator can't be chained without parentheses, e.g. `a < b < c` is a syntax error.
+    None,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BinOpInfo {
+    pub op: BinOp,
+    pub token: TokenKind,
+
+    /// Operators with higher precedence bind more tightly.
+    pub precedence: u8,
+    pub assoc: Assoc,
+    pub source: &'static str,
+}
+
+/// Every binary operator, from most to least tightly binding. The precedences and associativities
+/// are the ones from Nix's parser.y.
+///
+/// `?` isn't in here because its right-hand side is an attribute path rather than an expression,
+/// and `-` appears only as subtraction, not negation.
+pub const BINARY_OPERATORS: [BinOpInfo; 15] = [
+    binop(BinOp::Concat,    TokenKind::Concat,    10, Assoc::Right, "++"),
+    binop(BinOp::Mult,      TokenKind::Mult,      9,  Assoc::Left,  "*"),
+    binop(BinOp::Divide,    TokenKind::Divide,    9,  Assoc::Left,  "/"),
+    binop(BinOp::Add,       TokenKind::Plus,      8,  Assoc::Left,  "+"),
+    binop(BinOp::Subtract,  TokenKind::Minus,     8,  Assoc::Left,  "-"),
+    binop(BinOp::Update,    TokenKind::Update,    7,  Assoc::Right, "//"),
+    binop(BinOp::Less,      TokenKind::Less,      6,  Assoc::None,  "<"),
+    binop(BinOp::LessEq,    TokenKind::LessEq,    6,  Assoc::None,  "<="),
+    binop(BinOp::Greater,   TokenKind::Greater,   6,  Assoc::None,  ">"),
+    binop(BinOp::GreaterEq, TokenKind::GreaterEq, 6,  Assoc::None,  ">="),
+    binop(BinOp::Equals,    TokenKind::Equals,    5,  Assoc::None,  "=="),
+    binop(BinOp::NotEquals, TokenKind::NotEquals, 5,  Assoc::None,  "!="),
+    binop(BinOp::And,       TokenKind::And,       4,  Assoc::Left,  "&&"),
+    binop(BinOp::Or,        TokenKind::Or,        3,  Assoc::Left,  "||"),
+    binop(BinOp::Implies,   TokenKind::Implies,   2,  Assoc::Right, "->"),
+];
+
+const fn binop(op: BinOp, token: TokenKind, precedence: u8, assoc: Assoc, source: &'static str)
+    -> BinOpInfo
+{
+    BinOpInfo { op, token, precedence, assoc, source }
+}
+
+impl BinOp {
+    /// The binary operator a token stands for, if any.
+    pub fn from_token(kind: TokenKind) -> Option<BinOp> {
+        BINARY_OPERATORS.iter().find(|info| info.token == kind).map(|info| info.op)
+    }
+
+    pub fn info(self) -> &'static BinOpInfo {
+        BINARY_OPERATORS.iter().find(|info| info.op == self).unwrap()
+    }
+
+    pub fn precedence(self) -> u8 {
+        self.info().precedence
+    }
+
+    pub fn assoc(self) -> Assoc {
+        self.info().assoc
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.info().source
+    }
+}
 
 // #[derive(Debug)]
 // pub enum ParseError {
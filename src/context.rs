@@ -1,7 +1,118 @@
-pub struct EvalContext {}
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::mem;
+use std::rc::Rc;
+
+use diagnostic::{Diagnostic, Severity};
+use path::{self, StorePathError, normalize_path};
+use source::SourceFile;
+use symbol::Symbol;
+
+pub struct EvalContext {
+    /// Every file lexed in this context, keyed by filename, so spans from any of them can be
+    /// resolved back to their source text.
+    files: RefCell<HashMap<Symbol, Rc<SourceFile>>>,
+
+    /// The directory `~` paths are resolved against. Defaults to `$HOME`.
+    home_dir: Option<String>,
+
+    /// The Nix store directory, exposed as `builtins.storeDir`. Defaults to `$NIX_STORE_DIR`, or
+    /// `/nix/store` if that isn't set.
+    store_dir: String,
+
+    /// Every warning reported with `warn` that hasn't been taken with `take_warnings` yet.
+    warnings: RefCell<Vec<Diagnostic>>,
+
+    /// Whether `warn` should fail instead of recording the warning, like Nix's `--abort-on-warn`.
+    warnings_as_errors: bool,
+}
 
 impl EvalContext {
     pub fn new() -> Self {
-        EvalContext {}
+        EvalContext {
+            files: RefCell::new(HashMap::new()),
+            home_dir: env::var("HOME").ok(),
+            store_dir: env::var("NIX_STORE_DIR").unwrap_or_else(|_| String::from("/nix/store")),
+            warnings: RefCell::new(Vec::new()),
+            warnings_as_errors: false,
+        }
+    }
+
+    pub fn home_dir(&self) -> Option<&str> {
+        self.home_dir.as_ref().map(|s| &s[..])
+    }
+
+    pub fn set_home_dir<S: Into<String>>(&mut self, home_dir: Option<S>) {
+        self.home_dir = home_dir.map(Into::into);
+    }
+
+    pub fn store_dir(&self) -> &str {
+        &self.store_dir
+    }
+
+    pub fn set_store_dir<S: Into<String>>(&mut self, store_dir: S) {
+        self.store_dir = store_dir.into();
+    }
+
+    /// Check that `path` is inside a syntactically valid store path under this context's store
+    /// directory and return that store path, as `builtins.storePath` does.
+    pub fn to_store_path(&self, path: &str) -> Result<String, StorePathError> {
+        path::to_store_path(&self.store_dir, path)
+    }
+
+    pub fn warnings_as_errors(&self) -> bool {
+        self.warnings_as_errors
+    }
+
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.warnings_as_errors = warnings_as_errors;
+    }
+
+    /// Report a warning, from `builtins.warn` or a deprecated language construct. Normally it's
+    /// recorded for `take_warnings`, but if warnings are errors it's returned as an error instead,
+    /// with its severity raised to match.
+    pub fn warn(&self, mut warning: Diagnostic) -> Result<(), Box<Diagnostic>> {
+        if self.warnings_as_errors {
+            warning.severity = Severity::Error;
+            return Err(Box::new(warning));
+        }
+        self.warnings.borrow_mut().push(warning);
+        Ok(())
+    }
+
+    /// Remove and return every warning reported so far, oldest first.
+    pub fn take_warnings(&self) -> Vec<Diagnostic> {
+        mem::take(&mut *self.warnings.borrow_mut())
+    }
+
+    /// Resolve the text of a path literal to the normalized absolute path it denotes. Relative
+    /// paths are relative to `base_dir` (usually the directory of the file they appear in) and
+    /// paths starting with `~/` are relative to the home directory.
+    ///
+    /// Returns `None` for a `~/` path when there is no home directory.
+    pub fn resolve_path(&self, path: &str, base_dir: &str) -> Option<String> {
+        let absolute = if let Some(home_relative) = path.strip_prefix("~/") {
+            format!("{}/{}", self.home_dir()?, home_relative)
+        } else if path.starts_with('/') {
+            path.to_owned()
+        } else {
+            format!("{}/{}", base_dir, path)
+        };
+        Some(normalize_path(&absolute))
+    }
+
+    /// Record the source of a file in the file registry, replacing any previous source registered
+    /// under the same filename.
+    pub fn register_file<S: Into<String>>(&self, filename: Symbol, source: S) -> Rc<SourceFile> {
+        let file = Rc::new(SourceFile::new(filename, source));
+        self.files.borrow_mut().insert(filename, file.clone());
+        file
+    }
+
+    /// Look up a file previously registered with `register_file` (which the lexer does for every
+    /// file it lexes).
+    pub fn file(&self, filename: Symbol) -> Option<Rc<SourceFile>> {
+        self.files.borrow().get(&filename).cloned()
     }
 }
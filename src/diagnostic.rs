@@ -0,0 +1,167 @@
+use std::cmp;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use context::EvalContext;
+use lex::Span;
+
+////////////////////////////////////////////////////////////////////////////////
+// Diagnostics
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A secondary span attached to a diagnostic, like "the attribute was first defined here".
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// An error, warning or note about some part of a Nix source file, shared by the lexer, parser and
+/// evaluator so they can all be reported the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// A short identifier for this kind of diagnostic, like `E0001`.
+    pub code: Option<&'static str>,
+
+    pub message: String,
+
+    /// The span the diagnostic is primarily about.
+    pub span: Span,
+
+    /// An optional message to show alongside the primary span.
+    pub primary_label: Option<String>,
+
+    /// Other related spans, each with its own message.
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(severity: Severity, span: Span, message: S) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            span,
+            primary_label: None,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn error<S: Into<String>>(span: Span, message: S) -> Self {
+        Diagnostic::new(Severity::Error, span, message)
+    }
+
+    pub fn warning<S: Into<String>>(span: Span, message: S) -> Self {
+        Diagnostic::new(Severity::Warning, span, message)
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_primary_label<S: Into<String>>(mut self, message: S) -> Self {
+        self.primary_label = Some(message.into());
+        self
+    }
+
+    pub fn with_label<S: Into<String>>(mut self, span: Span, message: S) -> Self {
+        self.labels.push(Label { span, message: message.into() });
+        self
+    }
+
+    /// Render this diagnostic in the style of rustc's error messages, quoting each labelled line
+    /// from the source files registered in `ctx`. The primary span is underlined with `^` and
+    /// secondary spans with `-`. Spans in files `ctx` doesn't know about are shown by location
+    /// only.
+    pub fn render(&self, ctx: &EvalContext) -> String {
+        let mut out = String::new();
+
+        write!(out, "{}", self.severity).unwrap();
+        if let Some(code) = self.code {
+            write!(out, "[{}]", code).unwrap();
+        }
+        write!(out, ": {}", self.message).unwrap();
+
+        let max_line = self.labels.iter()
+            .map(|label| label.span.start.line)
+            .fold(self.span.start.line, cmp::max);
+        let gutter = " ".repeat(max_line.to_string().len());
+
+        let primary = (self.span, self.primary_label.as_ref().map(|s| &s[..]), '^');
+        let secondaries = self.labels.iter()
+            .map(|label| (label.span, Some(&label.message[..]), '-'));
+        let all_labels = Some(primary).into_iter().chain(secondaries);
+        let mut prev_filename = None;
+
+        for (i, (span, message, marker)) in all_labels.enumerate() {
+            // Like rustc, introduce the primary location with `-->` and any later location in a
+            // different file with `:::`.
+            if prev_filename != Some(span.filename) {
+                let arrow = if i == 0 { "-->" } else { ":::" };
+                write!(out, "\n{}{} {}:{}", gutter, arrow, span.filename, span.start).unwrap();
+            }
+            prev_filename = Some(span.filename);
+
+            let file = ctx.file(span.filename);
+            let line = match file.as_ref().and_then(|file| file.line(span.start.line)) {
+                Some(line) => line,
+                None => continue,
+            };
+
+            // Underline to the end of the span, or to the end of the line for multi-line spans.
+            // Empty spans still get a single marker so they're visible.
+            let line_len = line.chars().count();
+            let underline_end = if span.end.line == span.start.line {
+                span.end.column
+            } else {
+                line_len + 1
+            };
+            let underline_len = cmp::max(underline_end.saturating_sub(span.start.column), 1);
+
+            write!(out, "\n{} |", gutter).unwrap();
+            write!(out, "\n{:>width$} | {}", span.start.line, line, width = gutter.len()).unwrap();
+            write!(out, "\n{} | {}{}", gutter, " ".repeat(span.start.column - 1),
+                   marker.to_string().repeat(underline_len)).unwrap();
+            if let Some(message) = message {
+                write!(out, " {}", message).unwrap();
+            }
+        }
+
+        out
+    }
+}
+
+/// A one-line summary like `main.nix:3:5: error[E0001]: duplicate attribute`. Use `render` to
+/// quote the source as well.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.span.filename, self.span.start, self.severity)?;
+        if let Some(code) = self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for Diagnostic {}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+////////////////////////////////////////////////////////////////////////////////
+// FlakeRef - Parsed flake references.
+////////////////////////////////////////////////////////////////////////////////
+
+/// A flake reference like `github:NixOS/nixpkgs/nixos-unstable` or `path:./dir?dir=sub`, parsed
+/// into its parts without fetching anything.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlakeRef {
+    /// A name looked up in the flake registry: `nixpkgs`, `flake:nixpkgs/nixos-23.05` or
+    /// `nixpkgs/<ref>/<rev>`.
+    Indirect { id: String, params: FlakeParams },
+
+    /// A local directory: `path:./dir`, or any reference starting with `.` or `/`.
+    Path { path: String, params: FlakeParams },
+
+    /// A GitHub repository: `github:owner/repo` with an optional `/<ref-or-rev>`.
+    GitHub { owner: String, repo: String, params: FlakeParams },
+
+    /// A Git repository: `git+https://...`, `git+ssh://...`, `git+file://...` or `git+http://...`.
+    /// `url` is the repository's URL without the `git+` prefix or query parameters.
+    Git { url: String, params: FlakeParams },
+}
+
+/// The parameters any flake reference can have, whether they came from its query string (like
+/// `?ref=main`) or from its path (like the `main` in `github:owner/repo/main`).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlakeParams {
+    /// A branch or tag name.
+    pub ref_name: Option<String>,
+
+    /// A full Git commit hash.
+    pub rev: Option<String>,
+
+    /// The subdirectory of the source tree containing `flake.nix`.
+    pub dir: Option<String>,
+}
+
+impl FromStr for FlakeRef {
+    type Err = ParseFlakeRefError;
+
+    /// Parse a flake reference. Only the `ref`, `rev` and `dir` query parameters are supported,
+    /// and they aren't percent-decoded.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |message: &str| ParseFlakeRefError {
+            flake_ref: s.to_owned(),
+            message: message.to_owned(),
+        };
+
+        let (base, query) = match s.find('?') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        let mut params = parse_query(query).map_err(|m| error(&m))?;
+
+        let flake_ref = if let Some(url) = base.strip_prefix("git+") {
+            let valid_scheme = ["https://", "http://", "ssh://", "file://"].iter()
+                .any(|scheme| url.starts_with(scheme) && url.len() > scheme.len());
+            if !valid_scheme {
+                return Err(error("expected a `https`, `http`, `ssh` or `file` URL after `git+`"));
+            }
+            FlakeRef::Git { url: url.to_owned(), params }
+        } else if let Some(owner_and_repo) = base.strip_prefix("github:") {
+            let segments: Vec<&str> = owner_and_repo.split('/').collect();
+            if segments.len() < 2 || segments.len() > 3 || segments.iter().any(|s| s.is_empty()) {
+                return Err(error("expected `github:<owner>/<repo>` or \
+                                  `github:<owner>/<repo>/<ref-or-rev>`"));
+            }
+            if let Some(ref_or_rev) = segments.get(2) {
+                if params.ref_name.is_some() || params.rev.is_some() {
+                    return Err(error("a ref or rev can't be given in both the path and the query"));
+                }
+                set_ref_or_rev(&mut params, ref_or_rev);
+            }
+            FlakeRef::GitHub {
+                owner: segments[0].to_owned(),
+                repo: segments[1].to_owned(),
+                params,
+            }
+        } else if base.starts_with("path:") || base.starts_with('.') || base.starts_with('/') {
+            let path = base.strip_prefix("path:").unwrap_or(base);
+            if path.is_empty() {
+                return Err(error("expected a path after `path:`"));
+            }
+            if params.ref_name.is_some() {
+                return Err(error("path flake references can't have a ref"));
+            }
+            FlakeRef::Path { path: path.to_owned(), params }
+        } else {
+            let id_and_rest = base.strip_prefix("flake:").unwrap_or(base);
+            let segments: Vec<&str> = id_and_rest.split('/').collect();
+            if !is_flake_id(segments[0]) {
+                return Err(error("unrecognized flake reference type"));
+            }
+            if segments.len() > 3 || segments.iter().any(|s| s.is_empty()) {
+                return Err(error("expected `<id>`, `<id>/<ref-or-rev>` or `<id>/<ref>/<rev>`"));
+            }
+            if segments.len() > 1 && (params.ref_name.is_some() || params.rev.is_some()) {
+                return Err(error("a ref or rev can't be given in both the path and the query"));
+            }
+            match segments.len() {
+                2 => set_ref_or_rev(&mut params, segments[1]),
+                3 => {
+                    if !is_rev(segments[2]) {
+                        return Err(error("expected a full commit hash after the ref"));
+                    }
+                    params.ref_name = Some(segments[1].to_owned());
+                    params.rev = Some(segments[2].to_owned());
+                }
+                _ => {}
+            }
+            FlakeRef::Indirect { id: segments[0].to_owned(), params }
+        };
+
+        Ok(flake_ref)
+    }
+}
+
+/// Parse `ref`, `rev` and `dir` query parameters into `FlakeParams`, returning an error message
+/// for anything else.
+fn parse_query(query: Option<&str>) -> Result<FlakeParams, String> {
+    let mut params = FlakeParams::default();
+    let query = match query {
+        Some(query) => query,
+        None => return Ok(params),
+    };
+
+    for pair in query.split('&') {
+        let (key, value) = match pair.find('=') {
+            Some(i) if i + 1 < pair.len() => (&pair[..i], &pair[i + 1..]),
+            _ => return Err(format!("expected `<name>=<value>` in query, found `{}`", pair)),
+        };
+        let field = match key {
+            "ref" => &mut params.ref_name,
+            "rev" => {
+                if !is_rev(value) {
+                    return Err(format!("`{}` isn't a full commit hash", value));
+                }
+                &mut params.rev
+            }
+            "dir" => &mut params.dir,
+            _ => return Err(format!("unsupported query parameter `{}`", key)),
+        };
+        if field.is_some() {
+            return Err(format!("query parameter `{}` given more than once", key));
+        }
+        *field = Some(value.to_owned());
+    }
+
+    Ok(params)
+}
+
+/// A path segment after the repository or flake ID is a rev if it looks like a commit hash, and a
+/// ref otherwise.
+fn set_ref_or_rev(params: &mut FlakeParams, ref_or_rev: &str) {
+    if is_rev(ref_or_rev) {
+        params.rev = Some(ref_or_rev.to_owned());
+    } else {
+        params.ref_name = Some(ref_or_rev.to_owned());
+    }
+}
+
+fn is_rev(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_flake_id(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseFlakeRefError {
+    pub flake_ref: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParseFlakeRefError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid flake reference '{}': {}", self.flake_ref, self.message)
+    }
+}
+
+impl Error for ParseFlakeRefError {}
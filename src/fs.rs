@@ -0,0 +1,60 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The type of a file as `builtins.readFileType` and `builtins.readDir` report it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FileType {
+    Regular,
+    Directory,
+    Symlink,
+    /// Anything else, like a socket, FIFO or device file.
+    Unknown,
+}
+
+impl FileType {
+    /// The string Nix uses for this type, like `"regular"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FileType::Regular => "regular",
+            FileType::Directory => "directory",
+            FileType::Symlink => "symlink",
+            FileType::Unknown => "unknown",
+        }
+    }
+
+    fn from_std(file_type: fs::FileType) -> FileType {
+        if file_type.is_symlink() {
+            FileType::Symlink
+        } else if file_type.is_dir() {
+            FileType::Directory
+        } else if file_type.is_file() {
+            FileType::Regular
+        } else {
+            FileType::Unknown
+        }
+    }
+}
+
+/// The type of the file at `path` like `builtins.readFileType`. A symlink is reported as
+/// `Symlink` rather than as the type of its target, so a dangling symlink isn't an error.
+pub fn read_file_type(path: &Path) -> io::Result<FileType> {
+    fs::symlink_metadata(path).map(|metadata| FileType::from_std(metadata.file_type()))
+}
+
+/// The names and types of the entries in the directory at `path` like `builtins.readDir`.
+/// Symlinks are reported as `Symlink` rather than as the type of their targets, as in
+/// `read_file_type`. Names that aren't valid UTF-8 are converted lossily.
+///
+/// The entries are sorted by name, whatever order the filesystem lists them in, so evaluations
+/// that turn them into lists (like `attrNames (readDir dir)`) are reproducible across machines.
+pub fn read_dir(path: &Path) -> io::Result<Vec<(String, FileType)>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        entries.push((name, FileType::from_std(entry.file_type()?)));
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
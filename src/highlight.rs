@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use lex::{Token, TokenKind};
+
+////////////////////////////////////////////////////////////////////////////////
+// Highlight - Token categories for syntax highlighting.
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Highlight {
+    Keyword,
+    /// String quotes and literal string text, but not the expressions interpolated into them.
+    String,
+    Number,
+    /// Path and URI literals.
+    Path,
+    Comment,
+    Operator,
+}
+
+impl Highlight {
+    /// The highlight for a kind of token, or `None` if it should be shown as plain text, like
+    /// identifiers and punctuation.
+    pub fn of(kind: TokenKind) -> Option<Highlight> {
+        match kind {
+            TokenKind::Integer | TokenKind::Float => Some(Highlight::Number),
+            TokenKind::Path | TokenKind::Uri => Some(Highlight::Path),
+            TokenKind::StrPart(_) | TokenKind::Quote(_) => Some(Highlight::String),
+            _ if kind.is_trivia() => Some(Highlight::Comment),
+            _ if kind.is_keyword() => Some(Highlight::Keyword),
+            _ if kind.is_operator() => Some(Highlight::Operator),
+            _ => None,
+        }
+    }
+
+    /// The CSS class `to_html` gives this highlight's spans.
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "nix-keyword",
+            Highlight::String => "nix-string",
+            Highlight::Number => "nix-number",
+            Highlight::Path => "nix-path",
+            Highlight::Comment => "nix-comment",
+            Highlight::Operator => "nix-operator",
+        }
+    }
+
+    /// The SGR parameters `to_ansi` uses for this highlight.
+    fn ansi_style(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "1;35", // Bold magenta.
+            Highlight::String => "32",    // Green.
+            Highlight::Number => "36",    // Cyan.
+            Highlight::Path => "33",      // Yellow.
+            Highlight::Comment => "90",   // Bright black.
+            Highlight::Operator => "34",  // Blue.
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Highlighters
+////////////////////////////////////////////////////////////////////////////////
+
+/// Highlight `source` as HTML, wrapping each highlighted run of text in a
+/// `<span class="nix-...">` (see `Highlight::css_class`) and escaping everything. The result
+/// contains no surrounding element, so it can be put in a `<pre>` or `<code>` as needed.
+///
+/// `tokens` must be the complete token stream lexed from `source`. Text between tokens, like
+/// whitespace, is copied through unhighlighted.
+pub fn to_html(source: &str, tokens: &[Token]) -> String {
+    let mut out = String::new();
+    highlight(source, tokens, |text, highlight| {
+        match highlight {
+            Some(h) => {
+                write!(out, "<span class=\"{}\">", h.css_class()).unwrap();
+                escape_html(&mut out, text);
+                out.push_str("</span>");
+            }
+            None => escape_html(&mut out, text),
+        }
+    });
+    out
+}
+
+/// Highlight `source` with ANSI escape codes for display in a terminal. `tokens` must be the
+/// complete token stream lexed from `source`, as for `to_html`.
+pub fn to_ansi(source: &str, tokens: &[Token]) -> String {
+    let mut out = String::new();
+    highlight(source, tokens, |text, highlight| {
+        match highlight {
+            Some(h) => write!(out, "\x1b[{}m{}\x1b[0m", h.ansi_style(), text).unwrap(),
+            None => out.push_str(text),
+        }
+    });
+    out
+}
+
+/// Split `source` into runs of text with the same highlight and pass each to `emit` in order.
+/// Adjacent tokens with the same highlight are merged into one run, so a string like `"hi"` is
+/// emitted whole rather than as its quotes and contents separately.
+fn highlight<'src, F>(source: &'src str, tokens: &[Token<'src>], mut emit: F)
+    where F: FnMut(&'src str, Option<Highlight>)
+{
+    let mut run_start = 0;
+    let mut run_highlight = None;
+    let mut offset = 0;
+
+    for token in tokens {
+        let start = token.span.start.offset;
+        let token_highlight = Highlight::of(token.kind);
+
+        // Any gap between tokens has no highlight, so it ends the current run.
+        if start > offset || token_highlight != run_highlight {
+            if offset > run_start {
+                emit(&source[run_start..offset], run_highlight);
+            }
+            if start > offset {
+                emit(&source[offset..start], None);
+            }
+            run_start = start;
+            run_highlight = token_highlight;
+        }
+        offset = token.span.end.offset;
+    }
+
+    if offset > run_start {
+        emit(&source[run_start..offset], run_highlight);
+    }
+    if source.len() > offset {
+        emit(&source[offset..], None);
+    }
+}
+
+fn escape_html(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
@@ -1,28 +1,70 @@
 use itertools::Itertools;
 use regex::{Regex, RegexSet};
+use std::cmp::Ordering;
+use std::error::Error;
 use std::fmt::{self, Write};
-use std::str::Chars;
+use std::str::{Chars, FromStr};
 
 use context::EvalContext;
+use diagnostic::Diagnostic;
+use source::SourceFile;
 use symbol::Symbol;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Token positions and spans
 ////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+/// A position in a source file.
+///
+/// Lines and columns start from 1. Columns count chars (Unicode scalar values), not bytes or
+/// graphemes, so `🎉` is one column and `e` followed by a combining accent is two. Nix itself counts
+/// bytes, but char columns line up with what editors and our own diagnostic underlines show for
+/// most text, and the exact byte position is always available as `offset`.
+///
+/// A position's identity is its line and column. The offset is extra data that isn't compared, so
+/// a `Pos` parsed from `"line:column"` (which can't know its offset) equals the lexer's `Pos` for
+/// the same place.
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Pos {
+    /// The 1-based column, in chars.
     pub column: usize,
+
+    /// The 1-based line. Lines are separated by `\n`.
     pub line: usize,
+
+    /// The byte offset of this position from the start of the source. Not compared by `==` or
+    /// ordering.
+    pub offset: usize,
+}
+
+impl PartialEq for Pos {
+    fn eq(&self, other: &Pos) -> bool {
+        (self.line, self.column) == (other.line, other.column)
+    }
 }
 
+impl Eq for Pos {}
+
 impl fmt::Display for Pos {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}:{}", self.line, self.column)
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Positions are ordered by line, then column, consistently with `==`.
+impl Ord for Pos {
+    fn cmp(&self, other: &Pos) -> Ordering {
+        (self.line, self.column).cmp(&(other.line, other.column))
+    }
+}
+
+impl PartialOrd for Pos {
+    fn partial_cmp(&self, other: &Pos) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Span {
     pub filename: Symbol,
     pub start: Pos,
@@ -35,13 +77,121 @@ impl fmt::Display for Span {
     }
 }
 
+/// Prints spans like `file.nix:1:1-1:2` so test assertion failures are readable. That's everything
+/// `==` compares, so two spans that print the same are equal. The byte offsets are left out since
+/// `Pos` equality ignores them.
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.filename, self)
+    }
+}
+
+/// Spans are ordered by filename, then by start position, then by end position, so sorting a list
+/// of spans sorts them by where they appear in each file.
+impl Ord for Span {
+    fn cmp(&self, other: &Span) -> Ordering {
+        self.filename.as_str().cmp(other.filename.as_str())
+            .then(self.start.cmp(&other.start))
+            .then(self.end.cmp(&other.end))
+    }
+}
+
+impl PartialOrd for Span {
+    fn partial_cmp(&self, other: &Span) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parses the `line:column` form produced by `Pos`'s `Display` impl. The byte offset isn't part of
+/// that form, so it's left as 0, which doesn't affect comparisons. Use `LineIndex::offset` to
+/// compute it if it's needed for slicing the source.
+impl FromStr for Pos {
+    type Err = ParsePosError;
+
+    fn from_str(s: &str) -> Result<Pos, ParsePosError> {
+        let (line, column) = s.split_once(':').ok_or(ParsePosError)?;
+        let line = line.parse().map_err(|_| ParsePosError)?;
+        let column = column.parse().map_err(|_| ParsePosError)?;
+
+        // Lines and columns are 1-based.
+        if line == 0 || column == 0 {
+            return Err(ParsePosError);
+        }
+
+        Ok(Pos { line, column, offset: 0 })
+    }
+}
+
+impl Span {
+    /// Whether `pos` lies within this span. Spans are half-open: the start position is inside the
+    /// span and the end position (just past its last char) is not, so an empty span contains
+    /// nothing.
+    ///
+    /// Positions don't record a filename, so it's up to the caller to only ask about positions in
+    /// the same file.
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.start <= pos && pos < self.end
+    }
+
+    /// Whether this span and `other` are in the same file and share at least one char. Like
+    /// `contains`, this treats an empty span as holding nothing, so it overlaps nothing, even a span
+    /// around it.
+    pub fn overlaps(&self, other: Span) -> bool {
+        self.filename == other.filename
+            && self.start < self.end && other.start < other.end
+            && self.start < other.end && other.start < self.end
+    }
+
+    /// Parses the `line:column-line:column` form produced by `Span`'s `Display` impl. The filename
+    /// isn't part of that form, so it must be supplied separately.
+    pub fn parse(filename: Symbol, s: &str) -> Result<Span, ParsePosError> {
+        let (start, end) = s.split_once('-').ok_or(ParsePosError)?;
+        Ok(Span { filename, start: start.parse()?, end: end.parse()? })
+    }
+}
+
+/// The error returned when parsing a `Pos` or `Span` from a string fails.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParsePosError;
+
+impl fmt::Display for ParsePosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid position, expected `line:column` or `line:column-line:column`")
+    }
+}
+
+impl Error for ParsePosError {}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Token<'src> {
     pub kind: TokenKind,
     pub span: Span,
+
+    /// The exact source text of the token. The lexer never interprets literals, so numbers keep
+    /// their original spelling (`1.50`, `007`, `2.5e3`) and a formatter can reproduce them
+    /// losslessly. Converting them to values, and any canonicalization, is up to the parser.
     pub source: &'src str,
 }
 
+impl<'src> Token<'src> {
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Whether this token can be skipped without changing the meaning of the program. See
+    /// `TokenKind::is_trivia`.
+    pub fn is_trivia(&self) -> bool {
+        self.kind.is_trivia()
+    }
+}
+
+/// Lexing errors are boxed since diagnostics are much larger than tokens.
+pub type LexResult<'src> = Result<Token<'src>, Box<Diagnostic>>;
+
 ////////////////////////////////////////////////////////////////////////////////
 // Token kinds
 ////////////////////////////////////////////////////////////////////////////////
@@ -99,6 +249,43 @@ pub enum TokenKind {
     KeywordOr,
 }
 
+impl TokenKind {
+    /// Tokens that carry no meaning for the parser. Whitespace isn't tokenized at all, so this is
+    /// only comments.
+    pub fn is_trivia(self) -> bool {
+        self == TokenKind::Comment
+    }
+
+    pub fn is_keyword(self) -> bool {
+        use self::TokenKind::*;
+        matches!(self,
+            KeywordIf | KeywordThen | KeywordElse | KeywordAssert | KeywordWith | KeywordLet |
+            KeywordIn | KeywordRec | KeywordInherit | KeywordOr)
+    }
+
+    /// Prefix and infix operators in expressions, including `?` (has-attribute). Punctuation like
+    /// `=`, `.`, `:` and `;` isn't counted.
+    pub fn is_operator(self) -> bool {
+        use self::TokenKind::*;
+        matches!(self,
+            Mult | Minus | Plus | Divide | Less | Greater | LessEq | GreaterEq | Equals |
+            NotEquals | And | Or | Implies | Not | Update | Concat | Question)
+    }
+
+    /// Numbers, paths, URIs and the literal text parts of strings.
+    pub fn is_literal(self) -> bool {
+        use self::TokenKind::*;
+        matches!(self, Integer | Float | Path | Uri | StrPart(_))
+    }
+
+    /// Tokens that open or close a nested construct: brackets, string quotes and `${`.
+    pub fn is_delimiter(self) -> bool {
+        use self::TokenKind::*;
+        matches!(self,
+            Quote(_) | DollarBraceL | ParenL | ParenR | BracketL | BracketR | BraceL | BraceR)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum StringStyle {
     /// A `"`-delimited string.
@@ -113,7 +300,7 @@ pub enum StringStyle {
 ////////////////////////////////////////////////////////////////////////////////
 
 /// An iterator wrapping a `std::str::Chars` iterator which also keeps track of the current line
-/// and column position.
+/// and column position and byte offset.
 #[derive(Clone)]
 struct CharsPos<'a> {
     chars: Chars<'a>,
@@ -122,7 +309,7 @@ struct CharsPos<'a> {
 
 impl<'a> CharsPos<'a> {
     fn new(chars: Chars<'a>) -> Self {
-        CharsPos { chars: chars, pos: Pos { line: 1, column: 1 } }
+        CharsPos { chars: chars, pos: Pos { line: 1, column: 1, offset: 0 } }
     }
 
     fn as_str(&self) -> &'a str {
@@ -140,6 +327,9 @@ impl<'a> Iterator for CharsPos<'a> {
             Some(_) => { self.pos.column += 1; }
             None => {}
         }
+        if let Some(c) = opt_c {
+            self.pos.offset += c.len_utf8();
+        }
         opt_c
     }
 }
@@ -164,17 +354,15 @@ pub struct Lexer<'ctx, 'src> {
     chars: CharsPos<'src>,
     filename: Symbol,
 
+    /// The entire source being lexed.
+    source: &'src str,
+
     /// A record of the levels of nesting the lexer is currently in. The last state is the most
     /// deeply nested. An empty stack implies the lexer is at the normal top-level.
     state_stack: Vec<LexerState>,
 
-    /// The line/column location of the start of the token currently being scanned.
+    /// The location of the start of the token currently being scanned.
     token_start_pos: Pos,
-
-    /// The position in the source string of the token currently being scanned, as a slice
-    /// extending to the end of the source string. Once the end of the token is found, this is
-    /// sliced off at that point to give just the slice of the string containing the token.
-    token_start_str: &'src str,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -182,13 +370,35 @@ enum LexerState {
     /// When the lexer is at the top-level, not nested inside strings.
     Normal,
 
-    /// When the lexer is inside a string.
-    String(StringStyle),
+    /// When the lexer is inside a string. The span is that of the string's opening quote, for
+    /// error reporting.
+    String(StringStyle, Span),
 
     /// When the lexer is in a `${}` interpolation inside a string.
     Interpolation,
 }
 
+/// The context the start of the source is lexed in. Anything other than `Expression` is for lexing
+/// fragments of a larger file, such as the body of a string being edited in a language server.
+///
+/// Fragments aren't added to the file registry, since they'd replace the source of the file they
+/// came from. Their positions are relative to the start of the fragment, so the caller has to
+/// offset them to get positions in the enclosing file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LexerMode {
+    /// The source is a complete expression, as in a `.nix` file.
+    Expression,
+
+    /// The source starts inside a string with the given style, just after its opening quote. Since
+    /// the opening quote isn't part of the source, errors about it point at the start of the
+    /// source instead.
+    String(StringStyle),
+
+    /// The source starts inside a `${}` interpolation in a string with the given style, just after
+    /// the `${`.
+    Interpolation(StringStyle),
+}
+
 impl StringStyle {
     fn delimiter(self) -> &'static str {
         match self {
@@ -198,13 +408,16 @@ impl StringStyle {
     }
 }
 
+/// Lexing errors are yielded in place of the token they occur in. Errors at the end of the file
+/// (like an unterminated string) end the token stream, while any other error skips the offending
+/// char and carries on lexing after it.
 impl<'ctx, 'src> Iterator for Lexer<'ctx, 'src> {
-    type Item = Token<'src>;
+    type Item = LexResult<'src>;
 
-    fn next(&mut self) -> Option<Token<'src>> {
+    fn next(&mut self) -> Option<LexResult<'src>> {
         match self.state() {
             LexerState::Normal | LexerState::Interpolation => self.lex_normal(),
-            LexerState::String(_) => self.lex_string_part(),
+            LexerState::String(..) => self.lex_string_part(),
         }
     }
 }
@@ -241,14 +454,46 @@ lazy_static! {
 
 impl<'ctx, 'src> Lexer<'ctx, 'src> {
     pub fn new(ctx: &'ctx EvalContext, filename: &str, source: &'src str) -> Self {
+        Lexer::with_mode(ctx, filename, source, LexerMode::Expression)
+    }
+
+    pub fn with_mode(ctx: &'ctx EvalContext, filename: &str, source: &'src str, mode: LexerMode)
+        -> Self
+    {
+        let filename = Symbol::new(filename);
+        if mode == LexerMode::Expression {
+            ctx.register_file(filename, source);
+        }
+        Lexer::init(ctx, filename, source, mode)
+    }
+
+    /// Lex a file that's already in `ctx`'s file registry, borrowing its text instead of copying it
+    /// again. Tokens can be kept for as long as the `SourceFile` is, independently of the string
+    /// the file was originally created from.
+    pub fn from_file(ctx: &'ctx EvalContext, file: &'src SourceFile) -> Self {
+        Lexer::init(ctx, file.filename, &file.source, LexerMode::Expression)
+    }
+
+    fn init(ctx: &'ctx EvalContext, filename: Symbol, source: &'src str, mode: LexerMode)
+        -> Self
+    {
         let chars = CharsPos::new(source.chars());
+        let start = Span { filename, start: chars.pos, end: chars.pos };
+        let state_stack = match mode {
+            LexerMode::Expression => vec![],
+            LexerMode::String(style) => vec![LexerState::String(style, start)],
+            LexerMode::Interpolation(style) => {
+                vec![LexerState::String(style, start), LexerState::Interpolation]
+            }
+        };
+
         Lexer {
             _ctx: ctx,
             token_start_pos: chars.pos,
-            token_start_str: source,
             chars,
-            filename: Symbol::new(filename),
-            state_stack: Vec::new(),
+            filename,
+            source,
+            state_stack,
         }
     }
 
@@ -256,7 +501,7 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
         *self.state_stack.last().unwrap_or(&LexerState::Normal)
     }
 
-    fn lex_normal(&mut self) -> Option<Token<'src>> {
+    fn lex_normal(&mut self) -> Option<LexResult<'src>> {
         debug_assert!(self.state() == LexerState::Normal ||
                       self.state() == LexerState::Interpolation);
         self.start_token();
@@ -264,7 +509,7 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
         macro_rules! simple {
             ($kind:ident, $len:expr) => ({
                 self.skip($len);
-                Some(self.finish_token(TokenKind::$kind))
+                Some(Ok(self.finish_token(TokenKind::$kind)))
             })
         }
 
@@ -291,15 +536,14 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
             };
 
             self.skip(token_str.len());
-            return Some(self.finish_token(token_kind));
+            return Some(Ok(self.finish_token(token_kind)));
         }
 
         let c1 = match self.peek(0) {
             Some(c) => c,
             None => {
                 if self.state() == LexerState::Interpolation {
-                    // TODO(solson): Report unterminated string hitting end of file.
-                    panic!("unterminated string hit end of file (inside Interpolation)");
+                    return Some(Err(self.unterminated_string_error()));
                 } else {
                     return None;
                 }
@@ -313,7 +557,7 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
                 self.lex_normal()
             }
 
-            ('#', _) => Some(self.lex_line_comment()),
+            ('#', _) => Some(Ok(self.lex_line_comment())),
             ('/', Some('*')) => Some(self.lex_long_comment()),
 
             ('/', Some('/')) => simple!(Update, 2),
@@ -349,16 +593,18 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
 
             // The beginning of a string.
             ('"', _) => {
-                self.state_stack.push(LexerState::String(StringStyle::Normal));
                 self.skip(1);
-                Some(self.finish_token(TokenKind::Quote(StringStyle::Normal)))
+                let quote = self.finish_token(TokenKind::Quote(StringStyle::Normal));
+                self.state_stack.push(LexerState::String(StringStyle::Normal, quote.span));
+                Some(Ok(quote))
             }
 
             // The beginning of an indent string.
             ('\'', Some('\'')) => {
-                self.state_stack.push(LexerState::String(StringStyle::Indent));
                 self.skip(2);
-                Some(self.finish_token(TokenKind::Quote(StringStyle::Indent)))
+                let quote = self.finish_token(TokenKind::Quote(StringStyle::Indent));
+                self.state_stack.push(LexerState::String(StringStyle::Indent, quote.span));
+                Some(Ok(quote))
             }
 
             // If we're lexing inside of a string interpolation, we need to keep track of our depth
@@ -389,13 +635,17 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
                 simple!(BraceR, 1)
             }
 
-            (c, _) => panic!("unhandled char: {}, at: {}", c, self.pos()),
+            (c, _) => {
+                self.skip(1);
+                let message = format!("unexpected character `{}`", c);
+                Some(Err(Box::new(Diagnostic::error(self.token_span(), message))))
+            }
         }
     }
 
-    fn lex_string_part(&mut self) -> Option<Token<'src>> {
+    fn lex_string_part(&mut self) -> Option<LexResult<'src>> {
         let string_style = match self.state() {
-            LexerState::String(style) => style,
+            LexerState::String(style, _) => style,
             s => panic!("entered lex_string_part in a non-string lexing state: {:?}", s),
         };
         self.start_token();
@@ -410,8 +660,8 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
                         (Some('\''), _) => { self.skip(3); continue; }
                         (Some('\\'), Some(_)) => { self.skip(4); continue; }
                         (Some('\\'), None) => {
-                            // TODO(solson): Report character escape in string meeting end of file.
-                            panic!("character escape hit end of file");
+                            self.skip(3);
+                            return Some(Err(self.unterminated_string_error()));
                         }
                         _ => {}
                     }
@@ -425,15 +675,12 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
 
                 self.skip(delimiter.len());
                 self.state_stack.pop(); // Pop the string state.
-                return Some(self.finish_token(TokenKind::Quote(string_style)));
+                return Some(Ok(self.finish_token(TokenKind::Quote(string_style))));
             }
 
             let c1 = match self.peek(0) {
                 Some(c) => c,
-                None => {
-                    // TODO(solson): Report string meeting end of file.
-                    panic!("unclosed string hit end of file");
-                }
+                None => return Some(Err(self.unterminated_string_error())),
             };
             let c2 = self.peek(1);
 
@@ -441,8 +688,8 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
                 ('\\', Some(_)) if string_style == StringStyle::Normal => self.skip(2),
 
                 ('\\', None) if string_style == StringStyle::Normal => {
-                    // TODO(solson): Report character escape (or string?) meeting end of file.
-                    panic!("character escape hit end of file");
+                    self.skip(1);
+                    return Some(Err(self.unterminated_string_error()));
                 }
 
                 ('\'', Some('\''))
@@ -454,7 +701,7 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
                     if self.token_start_pos == self.pos() {
                         self.skip(2); // Skip over the '${'.
                         self.state_stack.push(LexerState::Interpolation);
-                        return Some(self.finish_token(TokenKind::DollarBraceL));
+                        return Some(Ok(self.finish_token(TokenKind::DollarBraceL)));
                     } else {
                         break;
                     }
@@ -468,7 +715,29 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
             };
         }
 
-        Some(self.finish_token(TokenKind::StrPart(string_style)))
+        Some(Ok(self.finish_token(TokenKind::StrPart(string_style))))
+    }
+
+    /// Build the error for hitting the end of the file inside a string (perhaps within an
+    /// interpolation in it), pointing at the innermost string's opening quote. Since there's
+    /// nothing left to lex, this also resets the lexer to the top-level so the token stream ends.
+    fn unterminated_string_error(&mut self) -> Box<Diagnostic> {
+        let (style, open) = self.state_stack.iter().rev().filter_map(|&state| match state {
+            LexerState::String(style, open) => Some((style, open)),
+            _ => None,
+        }).next().expect("unterminated_string_error called outside of a string");
+        self.state_stack.clear();
+
+        Box::new(Diagnostic::error(open, "unterminated string")
+            .with_primary_label("string starts here")
+            .with_label(self.eof_span(), format!(
+                "expected `{}` before the end of the file", style.delimiter())))
+    }
+
+    /// An empty span at the current position, which should be the end of the file.
+    fn eof_span(&self) -> Span {
+        debug_assert!(self.peek(0).is_none());
+        Span { filename: self.filename, start: self.pos(), end: self.pos() }
     }
 
     /// Regexp from the Nix lexer: `[ \t\r\n]+`
@@ -486,21 +755,23 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
     }
 
     /// Regexp from the Nix lexer: `\/\*([^*]|\*[^\/])*\*\/`
-    fn lex_long_comment(&mut self) -> Token<'src> {
+    fn lex_long_comment(&mut self) -> LexResult<'src> {
         debug_assert!(self.peek_starts_with("/*"));
         self.start_token();
         self.skip(2);
+        let open = self.token_span();
 
         while !self.peek_starts_with("*/") {
             if self.peek(0).is_none() {
-                // TODO(tsion): Report unterminated comment meeting end of file.
-                panic!("unclosed string hit end of file");
+                return Err(Box::new(Diagnostic::error(open, "unterminated comment")
+                    .with_primary_label("comment starts here")
+                    .with_label(self.eof_span(), "expected `*/` before the end of the file")));
             }
             self.skip(1);
         }
         self.skip(2);
 
-        self.finish_token(TokenKind::Comment)
+        Ok(self.finish_token(TokenKind::Comment))
     }
 
     fn skip_while<F>(&mut self, mut f: F) -> usize where F: FnMut(char) -> bool {
@@ -533,44 +804,122 @@ impl<'ctx, 'src> Lexer<'ctx, 'src> {
     /// Record the start position of a token, to be paired with `finish_token`.
     fn start_token(&mut self) {
         self.token_start_pos = self.pos();
-        self.token_start_str = self.chars.as_str();
     }
 
     /// Generate a token starting at the start position from the most recent `start_token` call and
     /// ending at the current position.
     fn finish_token(&self, kind: TokenKind) -> Token<'src> {
-        // This slightly convoluted code takes two slices from certain positions in the source to
-        // the end of the source and figures out the distance between their starting positions by
-        // subtracting their distances to the end of the source.
-        let start_len = self.token_start_str.len();
-        let end_len = self.chars.as_str().len();
-        let len = start_len - end_len;
-        let source = &self.token_start_str[..len];
-
-        Token {
-            kind,
-            span: Span {
-                filename: self.filename,
-                start: self.token_start_pos,
-                end: self.pos(),
-            },
-            source,
+        let span = self.token_span();
+        Token { kind, span, source: self.source_slice(span) }
+    }
+
+    /// The span from the start position from the most recent `start_token` call to the current
+    /// position.
+    fn token_span(&self) -> Span {
+        Span {
+            filename: self.filename,
+            start: self.token_start_pos,
+            end: self.pos(),
         }
     }
 
-    // This function is used for lexer tests, so if you change the format of it, you must change
-    // the format of all the lexer tests (in tests/lexer/*.out).
+    /// The text of the source being lexed which `span` covers.
+    pub fn source_slice(&self, span: Span) -> &'src str {
+        source_slice(self.source, span)
+    }
+
+    // This function is used for lexer tests, so if you change the format of it (or of `Token`'s
+    // `Display` impl), you must change the format of all the lexer tests (in tests/lexer/*.out).
     pub fn debug_string(self) -> String {
         let mut out = String::new();
-        for Token { kind, span, source } in self {
-            let Span { start: s, end: e, .. } = span;
-            write!(out, "[{}:{}-{}:{}] ({:?}) {:?}\n",
-                s.line, s.column, e.line, e.column, kind, source).unwrap();
+        for result in self {
+            match result {
+                Ok(token) => writeln!(out, "{}", token).unwrap(),
+                Err(diagnostic) => {
+                    writeln!(out, "[{}] {}: {}",
+                        diagnostic.span, diagnostic.severity, diagnostic.message).unwrap();
+                }
+            }
         }
         out
     }
 }
 
+impl<'src> fmt::Display for Token<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] ({:?}) {:?}", self.span, self.kind, self.source)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Token stream diffing
+////////////////////////////////////////////////////////////////////////////////
+
+/// The first point at which two token streams differ.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TokenDiff<'src> {
+    /// The index of the first differing token.
+    pub index: usize,
+
+    /// The token at `index` in the left stream, or `None` if the left stream ended there.
+    pub left: Option<Token<'src>>,
+
+    /// The token at `index` in the right stream, or `None` if the right stream ended there.
+    pub right: Option<Token<'src>>,
+}
+
+impl<'src> fmt::Display for TokenDiff<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // If the tokens only differ in their byte offsets, which `Token`'s `Display` doesn't show,
+        // show the offsets too.
+        let show_offsets = self.left.is_some() && self.left == self.right;
+        let side = |f: &mut fmt::Formatter, label: &str, token: Option<Token>| match token {
+            Some(token) if show_offsets => write!(f, "\n  {} {} at bytes {}..{}", label, token,
+                                                  token.span.start.offset, token.span.end.offset),
+            Some(token) => write!(f, "\n  {} {}", label, token),
+            None => write!(f, "\n  {} <end of tokens>", label),
+        };
+
+        write!(f, "token streams differ at index {}:", self.index)?;
+        side(f, "left: ", self.left)?;
+        side(f, "right:", self.right)
+    }
+}
+
+/// Compare two token streams, returning the first point at which they differ, or `None` if they're
+/// identical. Tokens are compared by kind, source text and span, including the filename and the
+/// byte offsets. `Span` equality ignores offsets, but stale offsets are exactly what comparing a
+/// spliced re-lex against a full one should catch, since `source_slice` relies on them.
+pub fn diff_tokens<'src>(left: &[Token<'src>], right: &[Token<'src>]) -> Option<TokenDiff<'src>> {
+    let len = ::std::cmp::max(left.len(), right.len());
+    (0..len)
+        .map(|index| TokenDiff {
+            index,
+            left: left.get(index).cloned(),
+            right: right.get(index).cloned(),
+        })
+        .find(|diff| !same_token(diff.left, diff.right))
+}
+
+fn same_token(left: Option<Token>, right: Option<Token>) -> bool {
+    match (left, right) {
+        (Some(l), Some(r)) => {
+            l == r && l.span.start.offset == r.span.start.offset
+                && l.span.end.offset == r.span.end.offset
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// The text of `source` which `span` covers, using the span's byte offsets.
+///
+/// Panics if the span's offsets are out of bounds or don't lie on char boundaries, which can only
+/// happen if `span` came from some other source.
+pub fn source_slice(source: &str, span: Span) -> &str {
+    &source[span.start.offset..span.end.offset]
+}
+
 fn ident_or_keyword_from_str(str: &str) -> TokenKind {
     match str {
         "if"      => TokenKind::KeywordIf,
@@ -589,18 +938,16 @@ fn ident_or_keyword_from_str(str: &str) -> TokenKind {
 
 pub mod lalrpop {
     use super::{Pos, TokenKind};
+    use diagnostic::Diagnostic;
 
     pub struct Lexer<'ctx, 'src>(pub super::Lexer<'ctx, 'src>);
 
-    #[derive(Debug)]
-    pub enum LexerError {}
-
     impl<'ctx, 'src> Iterator for Lexer<'ctx, 'src> {
-        type Item = Result<(Pos, TokenKind, Pos), LexerError>;
+        type Item = Result<(Pos, TokenKind, Pos), Box<Diagnostic>>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            self.0.next().map(|token| {
-                Ok((token.span.start, token.kind, token.span.end))
+            self.0.next().map(|result| {
+                result.map(|token| (token.span.start, token.kind, token.span.end))
             })
         }
     }
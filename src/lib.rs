@@ -5,6 +5,16 @@ extern crate regex;
 
 pub mod ast;
 pub mod context;
+pub mod diagnostic;
+pub mod flakeref;
+pub mod fs;
+pub mod highlight;
 pub mod lex;
+pub mod list;
+pub mod number;
 pub mod parse; // Generated by LALRPOP from parse.lalrpop.
+pub mod path;
+pub mod source;
+pub mod strings;
 pub mod symbol;
+pub mod version;
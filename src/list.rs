@@ -0,0 +1,24 @@
+/// The first `count` elements of `list` like nixpkgs' `lib.take`, or all of them if there are
+/// fewer. This is `sublist(0, count, list)`.
+pub fn take<T>(count: usize, list: &[T]) -> &[T] {
+    sublist(0, count, list)
+}
+
+/// `list` without its first `count` elements like nixpkgs' `lib.drop`, or empty if there are
+/// fewer. This is `sublist(count, list.len(), list)`.
+pub fn drop<T>(count: usize, list: &[T]) -> &[T] {
+    sublist(count, list.len(), list)
+}
+
+/// The `count` elements of `list` starting at `start` like nixpkgs' `lib.sublist`, clamped to the
+/// end of the list: a `start` past the end gives an empty list, and a `count` past the end gives
+/// the rest of the list.
+///
+/// These only slice the list and never look at its elements, so a list of thunks stays unforced.
+/// Only the list's length is needed. Negative counts and starts are errors in Nix, which the
+/// primops should raise before converting to `usize`.
+pub fn sublist<T>(start: usize, count: usize, list: &[T]) -> &[T] {
+    let start = start.min(list.len());
+    let end = start.saturating_add(count).min(list.len());
+    &list[start..end]
+}
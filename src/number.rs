@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+
+/// The whitespace Nix's `[[:space:]]` matches, which `toInt` and `toFloat` trim from their input.
+const NIX_WHITESPACE: &[char] = &[' ', '\t', '\n', '\r', '\x0B', '\x0C'];
+
+/// Parse a string to an integer like nixpkgs' `lib.toInt`, for `builtins.toInt`. Surrounding
+/// whitespace is trimmed, and only an optional `-` followed by decimal digits is accepted. Leading
+/// zeros are rejected as ambiguous (they'd be octal in some languages), other than `0` itself.
+pub fn to_int(s: &str) -> Result<i64, ParseNumberError> {
+    let error = |reason| ParseNumberError { input: s.to_owned(), target: "an integer", reason };
+
+    let trimmed = s.trim_matches(NIX_WHITESPACE);
+    let digits = trimmed.strip_prefix('-').unwrap_or(trimmed);
+    if digits.is_empty() || !skip_digits(digits).is_empty() {
+        return Err(error(None));
+    }
+
+    if digits.len() > 1 && digits.starts_with('0') {
+        return Err(error(Some("leading zeros are ambiguous")));
+    }
+
+    trimmed.parse().map_err(|_| error(Some("it doesn't fit in 64 bits")))
+}
+
+/// Parse a string to a float, for `builtins.toFloat`. Surrounding whitespace is trimmed like in
+/// `to_int`, and the rest must be a decimal number with an optional fraction and exponent, like
+/// `3.14` or `-1e10`. Other spellings Rust would accept, like `inf`, `+1` or `.5`, are rejected.
+pub fn to_float(s: &str) -> Result<f64, ParseNumberError> {
+    let trimmed = s.trim_matches(NIX_WHITESPACE);
+    if !is_decimal_float(trimmed) {
+        return Err(ParseNumberError { input: s.to_owned(), target: "a float", reason: None });
+    }
+    Ok(trimmed.parse().unwrap())
+}
+
+/// Divide integers like `builtins.div`, truncating towards zero as in C, so `div (-7) 2` is `-3`.
+pub fn div(a: i64, b: i64) -> Result<i64, ArithmeticError> {
+    if b == 0 {
+        return Err(ArithmeticError::DivisionByZero);
+    }
+    a.checked_div(b).ok_or(ArithmeticError::Overflow)
+}
+
+/// The remainder of `div`, like nixpkgs' `lib.mod`, which is `a - b * div a b`. Since `div`
+/// truncates, the result has the sign of `a`: `mod 7 3` is `1` but `mod (-7) 3` is `-1`.
+pub fn modulo(a: i64, b: i64) -> Result<i64, ArithmeticError> {
+    if b == 0 {
+        return Err(ArithmeticError::DivisionByZero);
+    }
+    a.checked_rem(b).ok_or(ArithmeticError::Overflow)
+}
+
+/// Whether `s` matches `-?[0-9]+(\.[0-9]+)?([eE][-+]?[0-9]+)?`.
+fn is_decimal_float(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let mut rest = skip_digits(s);
+    if rest.len() == s.len() {
+        return false;
+    }
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = skip_digits(fraction);
+        if rest.len() == fraction.len() {
+            return false;
+        }
+    }
+
+    if let Some(exponent) = rest.strip_prefix(|c| c == 'e' || c == 'E') {
+        let exponent = exponent.strip_prefix(|c| c == '+' || c == '-').unwrap_or(exponent);
+        rest = skip_digits(exponent);
+        if rest.len() == exponent.len() {
+            return false;
+        }
+    }
+
+    rest.is_empty()
+}
+
+/// `s` without its leading ASCII digits.
+fn skip_digits(s: &str) -> &str {
+    s.trim_start_matches(|c: char| c.is_ascii_digit())
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseNumberError {
+    /// The string that couldn't be converted, before trimming.
+    pub input: String,
+
+    /// What it was being converted to, like `an integer`.
+    pub target: &'static str,
+
+    /// Why the conversion failed, if there's more to say than that the syntax is wrong.
+    pub reason: Option<&'static str>,
+}
+
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "could not convert {:?} to {}", self.input, self.target)?;
+        if let Some(reason) = self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseNumberError {}
+
+/// Why `div` or `modulo` failed. The primop reports it at the span of the operation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ArithmeticError {
+    DivisionByZero,
+    /// The result doesn't fit in 64 bits, as when dividing the minimum integer by `-1`.
+    Overflow,
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArithmeticError::DivisionByZero => write!(f, "division by zero"),
+            ArithmeticError::Overflow => write!(f, "integer overflow in division"),
+        }
+    }
+}
+
+impl Error for ArithmeticError {}
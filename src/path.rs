@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::fmt;
+
+/// Lexically normalize a path the way Nix's `canonPath` does: duplicate slashes and `.` components
+/// are removed, `..` removes the preceding component, and any trailing slash is dropped. The
+/// filesystem is never consulted, so symlinks aren't resolved.
+///
+/// Absolute paths keep a single leading slash, and `..` at the root stays at the root. Relative
+/// paths stay relative, keeping any leading `..` components they can't resolve, and normalize to
+/// `.` if nothing is left.
+pub fn normalize_path(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut components: Vec<&str> = Vec::new();
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => match components.last() {
+                Some(&last) if last != ".." => { components.pop(); }
+                _ if absolute => {}
+                _ => components.push(".."),
+            },
+            _ => components.push(component),
+        }
+    }
+
+    let joined = components.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else if joined.is_empty() {
+        String::from(".")
+    } else {
+        joined
+    }
+}
+
+/// The last component of a path, like `builtins.baseNameOf`. A single trailing slash is ignored,
+/// so `/a/b/` gives `b`, and a path without slashes is returned as-is. `/` gives the empty string,
+/// as in Nix.
+///
+/// This works on the text of both paths and strings, so the caller decides what type to return.
+pub fn base_name_of(path: &str) -> &str {
+    let path = path.strip_suffix('/').unwrap_or(path);
+    match path.rfind('/') {
+        Some(i) => &path[i + 1..],
+        None => path,
+    }
+}
+
+/// Everything before the last slash of a path, like `builtins.dirOf`. A path without slashes gives
+/// `.`, and a path whose only slash is the leading one (including `/` itself) gives `/`.
+///
+/// Path values are always normalized, so for them this is the parent directory. Strings are taken
+/// literally, so `dirOf "/a/b/"` is `/a/b`, just as in Nix.
+pub fn dir_of(path: &str) -> &str {
+    match path.rfind('/') {
+        None => ".",
+        Some(0) => "/",
+        Some(i) => &path[..i],
+    }
+}
+
+/// The digits of the base-32 encoding Nix uses for store path hashes. `e`, `o`, `u` and `t` are
+/// left out to avoid accidental words.
+const BASE32_DIGITS: &str = "0123456789abcdfghijklmnpqrsvwxyz";
+
+/// The length of the hash part of a store path's base name.
+const STORE_PATH_HASH_LEN: usize = 32;
+
+/// The maximum length of the name part of a store path's base name.
+const STORE_PATH_NAME_MAX_LEN: usize = 211;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StorePathError {
+    /// The path isn't inside the store directory at all.
+    NotInStore(String),
+
+    /// The store path's base name doesn't start with a valid hash followed by `-`.
+    InvalidHash(String),
+
+    /// The store path's name (after the hash) is empty, too long, starts with `.` or contains a
+    /// forbidden character.
+    InvalidName(String),
+}
+
+impl fmt::Display for StorePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StorePathError::NotInStore(ref path) =>
+                write!(f, "path '{}' is not in the Nix store", path),
+            StorePathError::InvalidHash(ref path) =>
+                write!(f, "store path '{}' has an invalid hash part", path),
+            StorePathError::InvalidName(ref path) =>
+                write!(f, "store path '{}' has an invalid name", path),
+        }
+    }
+}
+
+impl Error for StorePathError {}
+
+/// Find the store path a path is in, like Nix's `toStorePath`. The path is normalized first, then
+/// must lie inside `store_dir` and start with a syntactically valid store path of the form
+/// `<store_dir>/<hash>-<name>`, which is returned without any trailing subpath.
+///
+/// Only the syntax is checked. Whether the store path actually exists isn't.
+pub fn to_store_path(store_dir: &str, path: &str) -> Result<String, StorePathError> {
+    let path = normalize_path(path);
+    let store_dir = normalize_path(store_dir);
+
+    let base_name = match path[..].strip_prefix(&store_dir[..]).and_then(|p| p.strip_prefix('/')) {
+        Some(relative) if !relative.is_empty() => relative.split('/').next().unwrap(),
+        _ => return Err(StorePathError::NotInStore(path)),
+    };
+    let store_path = format!("{}/{}", store_dir, base_name);
+
+    let (hash, name) = match base_name.find('-') {
+        Some(i) => (&base_name[..i], &base_name[i + 1..]),
+        None => return Err(StorePathError::InvalidHash(store_path)),
+    };
+
+    if hash.len() != STORE_PATH_HASH_LEN || !hash.chars().all(|c| BASE32_DIGITS.contains(c)) {
+        return Err(StorePathError::InvalidHash(store_path));
+    }
+
+    let valid_name_char = |c: char| c.is_ascii_alphanumeric() || "+-._?=".contains(c);
+    if name.is_empty() || name.len() > STORE_PATH_NAME_MAX_LEN || name.starts_with('.')
+        || !name.chars().all(valid_name_char) {
+        return Err(StorePathError::InvalidName(store_path));
+    }
+
+    Ok(store_path)
+}
@@ -0,0 +1,135 @@
+use lex::{self, Pos, Span};
+use symbol::Symbol;
+
+////////////////////////////////////////////////////////////////////////////////
+// LineIndex - Precomputed line boundaries for a source file.
+////////////////////////////////////////////////////////////////////////////////
+
+/// The byte offsets at which each line of a source string starts, computed once per file so that
+/// converting between byte offsets and line/column positions doesn't have to re-scan the source.
+///
+/// Lines are delimited by `\n` only, just like in the lexer. Columns are counted in chars, starting
+/// from 1, so they agree with the `Pos`es the lexer produces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineIndex {
+    /// The byte offset of the start of each line. The first line always starts at 0, so this is
+    /// never empty.
+    line_starts: Vec<usize>,
+
+    /// The length in bytes of the indexed source.
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { line_starts, len: source.len() }
+    }
+
+    /// The number of lines in the source. A trailing newline starts a final, empty line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Convert a byte offset into `source` to a line/column position. Returns `None` if the offset
+    /// is past the end of the source or doesn't lie on a char boundary.
+    ///
+    /// `source` must be the same string this index was built from.
+    pub fn pos(&self, source: &str, offset: usize) -> Option<Pos> {
+        debug_assert_eq!(source.len(), self.len);
+        if offset > source.len() || !source.is_char_boundary(offset) {
+            return None;
+        }
+
+        // The line containing `offset` is the last one starting at or before it.
+        let line_index = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let line_start = self.line_starts[line_index];
+        let column = source[line_start..offset].chars().count() + 1;
+
+        Some(Pos { line: line_index + 1, column, offset })
+    }
+
+    /// Convert a line/column position to a byte offset into `source`, ignoring the position's own
+    /// `offset`. Returns `None` if the line doesn't exist or the column is past the end of the
+    /// line. The column just after the last char of a line (where its newline is, or the end of
+    /// the source) is allowed.
+    ///
+    /// `source` must be the same string this index was built from.
+    pub fn offset(&self, source: &str, pos: Pos) -> Option<usize> {
+        debug_assert_eq!(source.len(), self.len);
+        if pos.column == 0 {
+            return None;
+        }
+
+        let line_start = self.line_start(pos.line)?;
+        let line = self.line(source, pos.line)?;
+        let column_offset = if pos.column - 1 == line.chars().count() {
+            line.len()
+        } else {
+            line.char_indices().nth(pos.column - 1)?.0
+        };
+
+        Some(line_start + column_offset)
+    }
+
+    /// The byte offset where the given (1-based) line starts.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        self.line_starts.get(line - 1).cloned()
+    }
+
+    /// The text of the given (1-based) line, without its terminating newline.
+    ///
+    /// `source` must be the same string this index was built from.
+    pub fn line<'src>(&self, source: &'src str, line: usize) -> Option<&'src str> {
+        debug_assert_eq!(source.len(), self.len);
+        let start = self.line_start(line)?;
+        let end = match self.line_starts.get(line) {
+            Some(&next_start) => next_start - 1, // Exclude the '\n'.
+            None => source.len(),
+        };
+        Some(&source[start..end])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// SourceFile - A source file's text along with its line index.
+////////////////////////////////////////////////////////////////////////////////
+
+/// The owned text of a source file. The context's file registry keeps one of these (in an `Rc`) for
+/// every file lexed, so spans can be resolved back to source text long after the string they were
+/// lexed from is gone.
+#[derive(Debug)]
+pub struct SourceFile {
+    pub filename: Symbol,
+    pub source: String,
+    pub line_index: LineIndex,
+}
+
+impl SourceFile {
+    pub fn new<S: Into<String>>(filename: Symbol, source: S) -> Self {
+        let source = source.into();
+        SourceFile {
+            filename,
+            line_index: LineIndex::new(&source),
+            source,
+        }
+    }
+
+    /// The text `span` covers. See `lex::source_slice`.
+    pub fn slice(&self, span: Span) -> &str {
+        debug_assert_eq!(span.filename, self.filename);
+        lex::source_slice(&self.source, span)
+    }
+
+    /// The text of the given (1-based) line, without its terminating newline.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.line_index.line(&self.source, line)
+    }
+}
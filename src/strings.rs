@@ -0,0 +1,130 @@
+use std::error::Error;
+use std::fmt;
+
+/// Replace patterns in `s` like `builtins.replaceStrings`, given `(from, to)` pairs.
+///
+/// The string is scanned left to right. At each position, the first pair whose `from` matches
+/// there wins, its `to` is appended, and scanning continues after the match, so replacements never
+/// overlap and are never rescanned. An empty `from` matches everywhere, inserting its `to` before
+/// every char and at the end. Nix scans bytes, so there it would insert between the bytes of a
+/// multi-byte char; here it inserts between chars instead, keeping the result valid UTF-8.
+///
+/// Checking that `from` and `to` have the same length, and merging the string context of the
+/// replacements used, is up to the primop.
+pub fn replace_strings(s: &str, replacements: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    loop {
+        if let Some(&(from, to)) = replacements.iter().find(|&&(from, _)| rest.starts_with(from)) {
+            out.push_str(to);
+            if !from.is_empty() {
+                rest = &rest[from.len()..];
+                continue;
+            }
+        }
+
+        // Nothing matched, or only an empty pattern did, so copy the next char as-is.
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some(c) => out.push(c),
+            None => break,
+        }
+        rest = chars.as_str();
+    }
+
+    out
+}
+
+/// The length of `s` in bytes like `builtins.stringLength`, so `"é"` has length 2.
+pub fn string_length(s: &str) -> usize {
+    s.len()
+}
+
+/// The part of `s` starting at byte `start` and at most `len` bytes long, like
+/// `builtins.substring`. Like `string_length`, this counts bytes rather than chars, and like Nix it
+/// clamps to the end of the string: a `start` past the end gives `""`, and a negative `len` means
+/// the rest of the string.
+///
+/// Nix strings are bytes, so Nix would return half of a multi-byte char if the indices split one.
+/// That can't be represented as a `str`, so it's an error here instead.
+pub fn substring(start: i64, len: i64, s: &str) -> Result<&str, SubstringError> {
+    if start < 0 {
+        return Err(SubstringError::NegativeStart(start));
+    }
+
+    let start = (start as u64).min(s.len() as u64) as usize;
+    let end = if len < 0 { s.len() } else { start.saturating_add(len as usize).min(s.len()) };
+    for &offset in &[start, end] {
+        if !s.is_char_boundary(offset) {
+            return Err(SubstringError::SplitsChar(offset));
+        }
+    }
+    Ok(&s[start..end])
+}
+
+/// Whether `s` starts with `prefix`, like nixpkgs' `lib.hasPrefix`.
+pub fn has_prefix(prefix: &str, s: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+/// Whether `s` ends with `suffix`, like nixpkgs' `lib.hasSuffix`.
+pub fn has_suffix(suffix: &str, s: &str) -> bool {
+    s.ends_with(suffix)
+}
+
+/// `s` without `prefix` like nixpkgs' `lib.removePrefix`, or `s` unchanged if it doesn't start
+/// with `prefix`.
+pub fn remove_prefix<'a>(prefix: &str, s: &'a str) -> &'a str {
+    s.strip_prefix(prefix).unwrap_or(s)
+}
+
+/// `s` without `suffix` like nixpkgs' `lib.removeSuffix`, or `s` unchanged if it doesn't end with
+/// `suffix`.
+pub fn remove_suffix<'a>(suffix: &str, s: &'a str) -> &'a str {
+    s.strip_suffix(suffix).unwrap_or(s)
+}
+
+/// `s` if `cond` holds and `""` otherwise, like nixpkgs' `lib.optionalString`.
+pub fn optional_string(cond: bool, s: &str) -> &str {
+    if cond { s } else { "" }
+}
+
+/// Map `f` over `items` and join the results with `sep`, like nixpkgs' `lib.concatMapStringsSep`.
+/// This builds the result in one buffer, where the Nix version builds a list and then joins it.
+pub fn concat_map_strings_sep<I, F, S>(sep: &str, mut f: F, items: I) -> String
+    where I: IntoIterator, F: FnMut(I::Item) -> S, S: AsRef<str>
+{
+    let mut out = String::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            out.push_str(sep);
+        }
+        out.push_str(f(item).as_ref());
+    }
+    out
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubstringError {
+    /// The start position was negative.
+    NegativeStart(i64),
+
+    /// The substring would start or end at this byte offset, in the middle of a char.
+    SplitsChar(usize),
+}
+
+impl fmt::Display for SubstringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SubstringError::NegativeStart(start) => {
+                write!(f, "negative start position {} in 'substring'", start)
+            }
+            SubstringError::SplitsChar(offset) => {
+                write!(f, "'substring' would split a UTF-8 character at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for SubstringError {}
@@ -2,7 +2,13 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::Mutex;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+/// An interned string.
+///
+/// Symbols are interned in a single process-wide table rather than per `EvalContext`, so their
+/// indices depend on everything interned before them (including by other threads or tests) and
+/// shouldn't be relied on. Nothing exposes the index anyway: both `Debug` and `Display` print the
+/// symbol's string, which keeps the `Debug` output of tokens, spans and ASTs reproducible.
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
 pub struct Symbol {
     index: u32,
 }
@@ -0,0 +1,79 @@
+use std::cmp::Ordering;
+
+/// Split a derivation name into its name and version like `builtins.parseDrvName`. The version
+/// starts after the first `-` that is followed by something other than a letter, so
+/// `hello-2.10` gives `("hello", "2.10")` and `foo-bar-1.0-rc1` gives `("foo-bar", "1.0-rc1")`.
+/// Without such a dash, the version is empty.
+pub fn parse_drv_name(s: &str) -> (&str, &str) {
+    let version_dash = s.char_indices().find(|&(i, c)| {
+        c == '-' && s[i + 1..].chars().next().is_some_and(|next| !next.is_ascii_alphabetic())
+    });
+    match version_dash {
+        Some((i, _)) => (&s[..i], &s[i + 1..]),
+        None => (s, ""),
+    }
+}
+
+/// Split a version into its components like `builtins.splitVersion`. Components are separated by
+/// `.` and `-`, and by any change between digits and non-digits, so `2.3pre1` gives
+/// `["2", "3", "pre", "1"]`. These are the same components `compare_versions` compares.
+pub fn split_version(version: &str) -> Vec<&str> {
+    let mut components = Vec::new();
+    let mut rest = version;
+    while let Some((component, after)) = next_component(rest) {
+        components.push(component);
+        rest = after;
+    }
+    components
+}
+
+/// Compare two versions like `builtins.compareVersions`, component by component. Numeric
+/// components compare as numbers, a missing component sorts before a numeric one, `pre` sorts
+/// before everything else, non-numeric components sort before numeric ones (so `2.3a` < `2.3.1`),
+/// and any other components compare as strings.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (a_component, a_rest) = next_component(a).unwrap_or(("", ""));
+        let (b_component, b_rest) = next_component(b).unwrap_or(("", ""));
+        if a_component.is_empty() && b_component.is_empty() {
+            return Ordering::Equal;
+        }
+
+        if components_less(a_component, b_component) {
+            return Ordering::Less;
+        } else if components_less(b_component, a_component) {
+            return Ordering::Greater;
+        }
+        a = a_rest;
+        b = b_rest;
+    }
+}
+
+/// The next version component of `s` and the text after it, or `None` if only separators are left.
+fn next_component(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start_matches(['.', '-']);
+    let first = s.chars().next()?;
+    let end = if first.is_ascii_digit() {
+        s.find(|c: char| !c.is_ascii_digit())
+    } else {
+        s.find(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+    };
+    let end = end.unwrap_or(s.len());
+    Some((&s[..end], &s[end..]))
+}
+
+/// Nix's `componentsLT`. An empty component stands for a missing one.
+fn components_less(a: &str, b: &str) -> bool {
+    let a_number = a.parse::<u64>().ok();
+    let b_number = b.parse::<u64>().ok();
+    match (a_number, b_number) {
+        (Some(a), Some(b)) => a < b,
+        (None, Some(_)) if a.is_empty() => true,
+        _ if a == "pre" && b != "pre" => true,
+        _ if b == "pre" => false,
+        (_, Some(_)) => true,
+        (Some(_), None) => false,
+        (None, None) => a < b,
+    }
+}
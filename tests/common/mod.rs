@@ -0,0 +1,8 @@
+use nixrs::context::EvalContext;
+use nixrs::lex::{Lexer, Token};
+
+/// Lex `source` as the file `filename`, panicking on any lexer error. Tests of the errors
+/// themselves use `Lexer` directly.
+pub fn lex<'src>(ctx: &EvalContext, filename: &str, source: &'src str) -> Vec<Token<'src>> {
+    Lexer::new(ctx, filename, source).map(Result::unwrap).collect()
+}
@@ -0,0 +1,100 @@
+extern crate nixrs;
+
+mod common;
+
+use std::error::Error;
+
+use nixrs::context::EvalContext;
+use nixrs::diagnostic::{Diagnostic, Severity};
+use nixrs::lex::{Lexer, Span};
+
+use common::lex;
+
+#[test]
+fn primary_and_secondary_labels() {
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "test.nix", "{\n  a = 1;\n  a = 2;\n}");
+    let first_a = tokens[1].span;
+    let second_a = tokens[5].span;
+
+    let diagnostic = Diagnostic::error(second_a, "attribute `a` already defined")
+        .with_code("E0001")
+        .with_primary_label("defined again here")
+        .with_label(first_a, "first defined here");
+
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.render(&ctx), "\
+error[E0001]: attribute `a` already defined
+ --> test.nix:3:3
+  |
+3 |   a = 2;
+  |   ^ defined again here
+  |
+2 |   a = 1;
+  |   - first defined here");
+}
+
+#[test]
+fn labels_in_other_files() {
+    let ctx = EvalContext::new();
+    let main = lex(&ctx, "main.nix", "import ./lib.nix");
+    let lib = lex(&ctx, "lib.nix", "throw \"oops\"");
+
+    let diagnostic = Diagnostic::warning(lib[0].span, "thrown from here")
+        .with_label(main[1].span, "imported here");
+
+    assert_eq!(diagnostic.render(&ctx), "\
+warning: thrown from here
+ --> lib.nix:1:1
+  |
+1 | throw \"oops\"
+  | ^^^^^
+ ::: main.nix:1:8
+  |
+1 | import ./lib.nix
+  |        --------- imported here");
+}
+
+#[test]
+fn multi_line_and_unknown_spans() {
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "test.nix", "x /* a\nlong comment */");
+    let comment = tokens[1].span;
+
+    let diagnostic = Diagnostic::error(comment, "unexpected comment");
+    assert_eq!(diagnostic.render(&ctx), "\
+error: unexpected comment
+ --> test.nix:1:3
+  |
+1 | x /* a
+  |   ^^^^");
+
+    // A span in a file that was never lexed is shown by location only.
+    let unknown = Span::parse(nixrs::symbol::Symbol::new("unknown.nix"), "4:2-4:3").unwrap();
+    let diagnostic = Diagnostic::error(unknown, "somewhere else");
+    assert_eq!(diagnostic.render(&ctx), "\
+error: somewhere else
+ --> unknown.nix:4:2");
+}
+
+#[test]
+fn display_and_error() {
+    fn lex_all(ctx: &EvalContext, source: &str) -> Result<usize, Box<dyn Error>> {
+        let mut count = 0;
+        for token in Lexer::new(ctx, "main.nix", source) {
+            token?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    let ctx = EvalContext::new();
+    assert_eq!(lex_all(&ctx, "a + b").unwrap(), 3);
+
+    let error = lex_all(&ctx, "{\n  x = \"oops;\n}").unwrap_err();
+    assert_eq!(error.to_string(), "main.nix:2:7: error: unterminated string");
+
+    let span = Span::parse(nixrs::symbol::Symbol::new("lib.nix"), "4:2-4:3").unwrap();
+    let warning = Diagnostic::warning(span, "deprecated").with_code("W0002");
+    assert_eq!(warning.to_string(), "lib.nix:4:2: warning[W0002]: deprecated");
+}
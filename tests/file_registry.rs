@@ -0,0 +1,58 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::{Lexer, TokenKind};
+use nixrs::symbol::Symbol;
+
+use common::lex;
+
+#[test]
+fn lexed_files_are_registered() {
+    let ctx = EvalContext::new();
+
+    let main_tokens = lex(&ctx, "main.nix", "import ./lib.nix");
+    let lib_tokens = lex(&ctx, "lib.nix", "{\n  x = throw \"oops\";\n}");
+
+    // Spans from either file resolve to the right source.
+    let path_span = main_tokens[1].span;
+    let main_file = ctx.file(path_span.filename).expect("main.nix wasn't registered");
+    assert_eq!(main_file.line(path_span.start.line), Some("import ./lib.nix"));
+
+    let throw_token = lib_tokens.iter().find(|t| t.source == "throw").unwrap();
+    assert_eq!(throw_token.kind, TokenKind::Identifier);
+    let lib_file = ctx.file(throw_token.span.filename).expect("lib.nix wasn't registered");
+    assert_eq!(lib_file.line(throw_token.span.start.line), Some("  x = throw \"oops\";"));
+}
+
+#[test]
+fn relexing_replaces_registered_source() {
+    let ctx = EvalContext::new();
+
+    let old_span = lex(&ctx, "edited.nix", "old")[0].span;
+    let new_span = lex(&ctx, "edited.nix", "new")[0].span;
+    assert_eq!(old_span.filename, new_span.filename);
+
+    let file = ctx.file(new_span.filename).unwrap();
+    assert_eq!(file.source, "new");
+}
+
+#[test]
+fn tokens_outlive_original_source() {
+    let ctx = EvalContext::new();
+
+    let file = {
+        let source = String::from("let x = \"hi\"; in x");
+        ctx.register_file(Symbol::new("scoped.nix"), source)
+    };
+    let tokens: Vec<_> = Lexer::from_file(&ctx, &file).map(Result::unwrap).collect();
+    assert_eq!(tokens[4].source, "hi");
+
+    // Spans lexed from a string that's since been dropped still resolve through the registry.
+    let span = {
+        let source = String::from("{ a = 1; }");
+        lex(&ctx, "dropped.nix", &source)[3].span
+    };
+    assert_eq!(ctx.file(span.filename).unwrap().slice(span), "1");
+}
@@ -0,0 +1,100 @@
+extern crate nixrs;
+
+use nixrs::flakeref::{FlakeParams, FlakeRef};
+
+const REV: &str = "0123456789abcdef0123456789abcdef01234567";
+
+fn parse(s: &str) -> FlakeRef {
+    s.parse().unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn params(ref_name: Option<&str>, rev: Option<&str>, dir: Option<&str>) -> FlakeParams {
+    FlakeParams {
+        ref_name: ref_name.map(Into::into),
+        rev: rev.map(Into::into),
+        dir: dir.map(Into::into),
+    }
+}
+
+#[test]
+fn indirect() {
+    assert_eq!(parse("nixpkgs"), FlakeRef::Indirect {
+        id: "nixpkgs".into(),
+        params: FlakeParams::default(),
+    });
+    assert_eq!(parse("flake:nixpkgs/nixos-23.05"), FlakeRef::Indirect {
+        id: "nixpkgs".into(),
+        params: params(Some("nixos-23.05"), None, None),
+    });
+    assert_eq!(parse(&format!("nixpkgs/nixos-unstable/{}", REV)), FlakeRef::Indirect {
+        id: "nixpkgs".into(),
+        params: params(Some("nixos-unstable"), Some(REV), None),
+    });
+}
+
+#[test]
+fn path() {
+    assert_eq!(parse("path:./dir"), FlakeRef::Path {
+        path: "./dir".into(),
+        params: FlakeParams::default(),
+    });
+    assert_eq!(parse("/etc/nixos?dir=hosts"), FlakeRef::Path {
+        path: "/etc/nixos".into(),
+        params: params(None, None, Some("hosts")),
+    });
+}
+
+#[test]
+fn github() {
+    assert_eq!(parse("github:NixOS/nixpkgs"), FlakeRef::GitHub {
+        owner: "NixOS".into(),
+        repo: "nixpkgs".into(),
+        params: FlakeParams::default(),
+    });
+    assert_eq!(parse("github:NixOS/nixpkgs/nixos-unstable?dir=lib"), FlakeRef::GitHub {
+        owner: "NixOS".into(),
+        repo: "nixpkgs".into(),
+        params: params(Some("nixos-unstable"), None, Some("lib")),
+    });
+    assert_eq!(parse(&format!("github:owner/repo/{}", REV)), FlakeRef::GitHub {
+        owner: "owner".into(),
+        repo: "repo".into(),
+        params: params(None, Some(REV), None),
+    });
+}
+
+#[test]
+fn git() {
+    assert_eq!(parse(&format!("git+https://example.org/repo.git?ref=main&rev={}", REV)),
+               FlakeRef::Git {
+                   url: "https://example.org/repo.git".into(),
+                   params: params(Some("main"), Some(REV), None),
+               });
+    assert_eq!(parse("git+ssh://git@example.org/repo"), FlakeRef::Git {
+        url: "ssh://git@example.org/repo".into(),
+        params: FlakeParams::default(),
+    });
+}
+
+#[test]
+fn malformed() {
+    let cases = [
+        ("github:NixOS", "expected `github:<owner>/<repo>` or \
+                          `github:<owner>/<repo>/<ref-or-rev>`"),
+        ("github:NixOS/nixpkgs/main?ref=dev", "a ref or rev can't be given in both the path and \
+                                              the query"),
+        ("git+ftp://example.org/repo", "expected a `https`, `http`, `ssh` or `file` URL after \
+                                        `git+`"),
+        ("nixpkgs?rev=abc", "`abc` isn't a full commit hash"),
+        ("nixpkgs?shallow=1", "unsupported query parameter `shallow`"),
+        ("path:./a?ref=main", "path flake references can't have a ref"),
+        ("1nixpkgs", "unrecognized flake reference type"),
+    ];
+
+    for &(flake_ref, message) in &cases {
+        let error = flake_ref.parse::<FlakeRef>().unwrap_err();
+        assert_eq!(error.message, message, "parsing {:?}", flake_ref);
+        assert_eq!(error.to_string(),
+                   format!("invalid flake reference '{}': {}", flake_ref, message));
+    }
+}
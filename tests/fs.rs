@@ -0,0 +1,61 @@
+extern crate nixrs;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use nixrs::fs::{read_dir, read_file_type, FileType};
+
+/// A fresh directory under the system temp directory, named for the test using it.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("nixrs-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_not_followed() {
+    use std::os::unix::fs::symlink;
+
+    let dir = temp_dir("symlinks");
+    fs::write(dir.join("file"), "").unwrap();
+    fs::create_dir(dir.join("subdir")).unwrap();
+    symlink(dir.join("file"), dir.join("link")).unwrap();
+    symlink(dir.join("subdir"), dir.join("dir-link")).unwrap();
+    symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+
+    assert_eq!(read_file_type(&dir.join("file")).unwrap(), FileType::Regular);
+    assert_eq!(read_file_type(&dir.join("subdir")).unwrap(), FileType::Directory);
+    assert_eq!(read_file_type(&dir.join("link")).unwrap(), FileType::Symlink);
+    assert_eq!(read_file_type(&dir.join("dir-link")).unwrap(), FileType::Symlink);
+    assert_eq!(read_file_type(&dir.join("dangling")).unwrap(), FileType::Symlink);
+    assert_eq!(FileType::Symlink.as_str(), "symlink");
+    assert!(read_file_type(&dir.join("missing")).is_err());
+
+    assert_eq!(read_dir(&dir).unwrap(), [
+        ("dangling".to_owned(), FileType::Symlink),
+        ("dir-link".to_owned(), FileType::Symlink),
+        ("file".to_owned(), FileType::Regular),
+        ("link".to_owned(), FileType::Symlink),
+        ("subdir".to_owned(), FileType::Directory),
+    ]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn read_dir_is_sorted() {
+    let dir = temp_dir("sorted");
+    for name in &["zeta", "b", "alpha", "C", "a.nix", "10", "9"] {
+        fs::write(dir.join(name), "").unwrap();
+    }
+
+    let names: Vec<String> = read_dir(&dir).unwrap().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["10", "9", "C", "a.nix", "alpha", "b", "zeta"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,35 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::highlight::{to_ansi, to_html};
+
+use common::lex;
+
+#[test]
+fn html() {
+    let ctx = EvalContext::new();
+    let source = "let s = \"<${x}>\"; # hi\nin s ++ [ 1 ./a ]";
+    let tokens = lex(&ctx, "test.nix", source);
+
+    assert_eq!(to_html(source, &tokens), concat!(
+        "<span class=\"nix-keyword\">let</span> s = ",
+        "<span class=\"nix-string\">&quot;&lt;</span>${x}",
+        "<span class=\"nix-string\">&gt;&quot;</span>",
+        "; <span class=\"nix-comment\"># hi</span>\n",
+        "<span class=\"nix-keyword\">in</span> s <span class=\"nix-operator\">++</span> [ ",
+        "<span class=\"nix-number\">1</span> <span class=\"nix-path\">./a</span> ]",
+    ));
+}
+
+#[test]
+fn ansi() {
+    let ctx = EvalContext::new();
+    let source = "if x then \"y\" else 2";
+    let tokens = lex(&ctx, "test.nix", source);
+
+    assert_eq!(to_ansi(source, &tokens),
+               "\x1b[1;35mif\x1b[0m x \x1b[1;35mthen\x1b[0m \x1b[32m\"y\"\x1b[0m \
+                \x1b[1;35melse\x1b[0m \x1b[36m2\x1b[0m");
+}
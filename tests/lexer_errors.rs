@@ -0,0 +1,98 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::diagnostic::{Diagnostic, Severity};
+use nixrs::lex::{LexResult, Lexer};
+
+/// Lex `source`, asserting that it produces exactly one error, as its last result.
+fn lex_error(ctx: &EvalContext, source: &str) -> Box<Diagnostic> {
+    let mut results: Vec<LexResult> = Lexer::new(ctx, "test.nix", source).collect();
+    let last = results.pop().expect("no tokens").expect_err("expected a lexing error");
+    for result in results {
+        result.expect("unexpected lexing error before the last token");
+    }
+    last
+}
+
+#[test]
+fn unterminated_normal_string() {
+    let ctx = EvalContext::new();
+    let error = lex_error(&ctx, "\"abc");
+
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(error.message, "unterminated string");
+    assert_eq!(error.span.to_string(), "1:1-1:2");
+    assert_eq!(error.primary_label.as_ref().unwrap(), "string starts here");
+    assert_eq!(error.labels.len(), 1);
+    assert_eq!(error.labels[0].span.to_string(), "1:5-1:5");
+    assert_eq!(error.labels[0].message, "expected `\"` before the end of the file");
+
+    assert_eq!(error.render(&ctx), "\
+error: unterminated string
+ --> test.nix:1:1
+  |
+1 | \"abc
+  | ^ string starts here
+  |
+1 | \"abc
+  |     - expected `\"` before the end of the file");
+}
+
+#[test]
+fn unterminated_indent_string() {
+    let ctx = EvalContext::new();
+    let error = lex_error(&ctx, "x + ''abc\n  def");
+
+    assert_eq!(error.message, "unterminated string");
+    assert_eq!(error.span.to_string(), "1:5-1:7");
+    assert_eq!(error.labels[0].span.to_string(), "2:6-2:6");
+    assert_eq!(error.labels[0].message, "expected `''` before the end of the file");
+}
+
+#[test]
+fn unterminated_string_in_interpolation() {
+    let ctx = EvalContext::new();
+    let error = lex_error(&ctx, "\"a ${ ''b ${ c");
+
+    // The error points at the innermost string.
+    assert_eq!(error.span.to_string(), "1:7-1:9");
+    assert_eq!(error.labels[0].span.to_string(), "1:15-1:15");
+    assert_eq!(error.labels[0].message, "expected `''` before the end of the file");
+}
+
+#[test]
+fn escape_at_end_of_file() {
+    let ctx = EvalContext::new();
+
+    let error = lex_error(&ctx, "\"abc\\");
+    assert_eq!(error.span.to_string(), "1:1-1:2");
+    assert_eq!(error.labels[0].span.to_string(), "1:6-1:6");
+
+    let error = lex_error(&ctx, "''abc''\\");
+    assert_eq!(error.span.to_string(), "1:1-1:3");
+    assert_eq!(error.labels[0].span.to_string(), "1:9-1:9");
+}
+
+#[test]
+fn unterminated_comment() {
+    let ctx = EvalContext::new();
+    let error = lex_error(&ctx, "a /* b");
+
+    assert_eq!(error.message, "unterminated comment");
+    assert_eq!(error.span.to_string(), "1:3-1:5");
+    assert_eq!(error.labels[0].span.to_string(), "1:7-1:7");
+    assert_eq!(error.labels[0].message, "expected `*/` before the end of the file");
+}
+
+#[test]
+fn unexpected_characters_are_skipped() {
+    let ctx = EvalContext::new();
+    let results: Vec<LexResult> = Lexer::new(&ctx, "test.nix", "a ` b").collect();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().source, "a");
+    let error = results[1].as_ref().unwrap_err();
+    assert_eq!(error.message, "unexpected character ```");
+    assert_eq!(error.span.to_string(), "1:3-1:4");
+    assert_eq!(results[2].as_ref().unwrap().source, "b");
+}
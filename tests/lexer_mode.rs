@@ -0,0 +1,80 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::{Lexer, LexerMode, StringStyle, TokenKind};
+use nixrs::symbol::Symbol;
+
+fn lex_kinds(source: &str, mode: LexerMode) -> Vec<(TokenKind, String)> {
+    let ctx = EvalContext::new();
+    Lexer::with_mode(&ctx, "fragment.nix", source, mode)
+        .map(|result| {
+            let token = result.unwrap();
+            (token.kind, token.source.to_owned())
+        })
+        .collect()
+}
+
+#[test]
+fn interpolation_mode() {
+    let tokens = lex_kinds("a}bc\"", LexerMode::Interpolation(StringStyle::Normal));
+    assert_eq!(tokens, [
+        (TokenKind::Identifier, "a".to_owned()),
+        (TokenKind::BraceR, "}".to_owned()),
+        (TokenKind::StrPart(StringStyle::Normal), "bc".to_owned()),
+        (TokenKind::Quote(StringStyle::Normal), "\"".to_owned()),
+    ]);
+}
+
+#[test]
+fn indent_interpolation_mode() {
+    let tokens = lex_kinds("{ }.x} y''", LexerMode::Interpolation(StringStyle::Indent));
+    assert_eq!(tokens, [
+        (TokenKind::BraceL, "{".to_owned()),
+        (TokenKind::BraceR, "}".to_owned()),
+        (TokenKind::Dot, ".".to_owned()),
+        (TokenKind::Identifier, "x".to_owned()),
+        (TokenKind::BraceR, "}".to_owned()),
+        (TokenKind::StrPart(StringStyle::Indent), " y".to_owned()),
+        (TokenKind::Quote(StringStyle::Indent), "''".to_owned()),
+    ]);
+}
+
+#[test]
+fn string_mode() {
+    let tokens = lex_kinds("x ${y} z\" + 1", LexerMode::String(StringStyle::Normal));
+    assert_eq!(tokens, [
+        (TokenKind::StrPart(StringStyle::Normal), "x ".to_owned()),
+        (TokenKind::DollarBraceL, "${".to_owned()),
+        (TokenKind::Identifier, "y".to_owned()),
+        (TokenKind::BraceR, "}".to_owned()),
+        (TokenKind::StrPart(StringStyle::Normal), " z".to_owned()),
+        (TokenKind::Quote(StringStyle::Normal), "\"".to_owned()),
+        (TokenKind::Plus, "+".to_owned()),
+        (TokenKind::Integer, "1".to_owned()),
+    ]);
+}
+
+#[test]
+fn expression_mode_is_the_default() {
+    let ctx = EvalContext::new();
+    let default: Vec<_> = Lexer::new(&ctx, "a.nix", "\"a}\"").collect();
+    let expression: Vec<_> =
+        Lexer::with_mode(&ctx, "a.nix", "\"a}\"", LexerMode::Expression).collect();
+    assert_eq!(default, expression);
+}
+
+#[test]
+fn fragments_are_not_registered() {
+    let ctx = EvalContext::new();
+    let _ = Lexer::new(&ctx, "main.nix", "{ s = \"hi ${x}\"; }").count();
+
+    // Lexing part of main.nix under its own name leaves the registered source alone.
+    let mode = LexerMode::String(StringStyle::Normal);
+    let _ = Lexer::with_mode(&ctx, "main.nix", "hi ${x}\"", mode).count();
+    let file = ctx.file(Symbol::new("main.nix")).unwrap();
+    assert_eq!(file.source, "{ s = \"hi ${x}\"; }");
+
+    let mode = LexerMode::Interpolation(StringStyle::Normal);
+    let _ = Lexer::with_mode(&ctx, "fragment.nix", "a}\"", mode).count();
+    assert!(ctx.file(Symbol::new("fragment.nix")).is_none());
+}
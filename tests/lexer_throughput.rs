@@ -0,0 +1,32 @@
+extern crate nixrs;
+
+use std::time::{Duration, Instant};
+
+use nixrs::context::EvalContext;
+use nixrs::lex::Lexer;
+use nixrs::symbol::Symbol;
+
+// The same input `benches/lexer.rs` measures.
+include!("../benches/support/large_fixture.rs");
+
+/// A smoke test for the benchmark input, with a time limit loose enough for unoptimized builds on
+/// slow machines. It only catches the lexer becoming drastically slower, like going quadratic;
+/// use `cargo bench` for real measurements.
+#[test]
+fn lex_large_file() {
+    let ctx = EvalContext::new();
+    let file = ctx.register_file(Symbol::new("large.nix"), large_fixture());
+    let start = Instant::now();
+
+    let mut tokens = 0;
+    for result in Lexer::from_file(&ctx, &file) {
+        if let Err(error) = result {
+            panic!("unexpected lexer error: {}", error.render(&ctx));
+        }
+        tokens += 1;
+    }
+
+    let elapsed = start.elapsed();
+    assert!(tokens > 10_000, "only lexed {} tokens", tokens);
+    assert!(elapsed < Duration::from_secs(10), "lexing took {:?}", elapsed);
+}
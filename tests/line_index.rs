@@ -0,0 +1,79 @@
+extern crate nixrs;
+
+use nixrs::lex::Pos;
+use nixrs::source::LineIndex;
+
+fn pos(line: usize, column: usize, offset: usize) -> Pos {
+    Pos { line, column, offset }
+}
+
+#[test]
+fn offsets_to_positions_and_back() {
+    let source = "let\n  a = 1;\nin a\n";
+    let index = LineIndex::new(source);
+    assert_eq!(index.line_count(), 4);
+
+    let cases = [
+        (0, 1, 1),  // `l`
+        (3, 1, 4),  // The first '\n'.
+        (4, 2, 1),  // The indentation before `a`.
+        (6, 2, 3),  // `a`
+        (13, 3, 1), // `in`
+        (16, 3, 4), // The last `a`.
+        (17, 3, 5), // The last '\n'.
+        (18, 4, 1), // The end of the source.
+    ];
+
+    for &(offset, line, column) in &cases {
+        let expected = pos(line, column, offset);
+        let actual = index.pos(source, offset);
+        assert_eq!(actual, Some(expected), "offset {}", offset);
+        assert_eq!(actual.unwrap().offset, offset);
+        assert_eq!(index.offset(source, expected), Some(offset), "pos {}", expected);
+    }
+
+    assert_eq!(index.pos(source, 19), None);
+    assert_eq!(index.offset(source, pos(2, 10, 0)), None);
+    assert_eq!(index.offset(source, pos(5, 1, 0)), None);
+    assert_eq!(index.offset(source, pos(1, 0, 0)), None);
+}
+
+#[test]
+fn multi_byte_chars() {
+    // 'é' is two bytes and '🎉' is four, but each counts as a single column.
+    let source = "\"é\" 🎉\nx";
+    let index = LineIndex::new(source);
+
+    let cases = [
+        (1, 1, 2),  // `é`
+        (3, 1, 3),  // The closing quote.
+        (5, 1, 5),  // `🎉`
+        (9, 1, 6),  // The '\n'.
+        (10, 2, 1), // `x`
+    ];
+
+    for &(offset, line, column) in &cases {
+        let expected = pos(line, column, offset);
+        let actual = index.pos(source, offset);
+        assert_eq!(actual, Some(expected), "offset {}", offset);
+        assert_eq!(actual.unwrap().offset, offset);
+        assert_eq!(index.offset(source, expected), Some(offset), "pos {}", expected);
+    }
+
+    // Offsets in the middle of a multi-byte char don't correspond to any position.
+    assert_eq!(index.pos(source, 2), None);
+    assert_eq!(index.pos(source, 7), None);
+}
+
+#[test]
+fn line_text() {
+    let source = "a\n\nbc\r\nd";
+    let index = LineIndex::new(source);
+
+    assert_eq!(index.line(source, 1), Some("a"));
+    assert_eq!(index.line(source, 2), Some(""));
+    assert_eq!(index.line(source, 3), Some("bc\r"));
+    assert_eq!(index.line(source, 4), Some("d"));
+    assert_eq!(index.line(source, 5), None);
+    assert_eq!(index.line(source, 0), None);
+}
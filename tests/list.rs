@@ -0,0 +1,35 @@
+extern crate nixrs;
+
+use nixrs::list::{drop, sublist, take};
+
+#[test]
+fn take_and_drop_clamp() {
+    assert_eq!(take(2, &[1, 2, 3]), [1, 2]);
+    assert_eq!(take(5, &[1, 2]), [1, 2]);
+    assert_eq!(take(0, &[1, 2]), [0; 0]);
+    assert_eq!(drop(1, &[1, 2, 3]), [2, 3]);
+    assert_eq!(drop(5, &[1, 2]), [0; 0]);
+    assert_eq!(drop(0, &[1, 2]), [1, 2]);
+}
+
+#[test]
+fn sublist_clamps() {
+    assert_eq!(sublist(1, 2, &[1, 2, 3, 4]), [2, 3]);
+    assert_eq!(sublist(2, 10, &[1, 2, 3, 4]), [3, 4]);
+    assert_eq!(sublist(4, 1, &[1, 2, 3, 4]), [0; 0]);
+    assert_eq!(sublist(10, 1, &[1, 2, 3, 4]), [0; 0]);
+    assert_eq!(sublist(1, usize::MAX, &[1, 2, 3, 4]), [2, 3, 4]);
+}
+
+#[test]
+fn elements_are_not_inspected() {
+    // Standing in for unevaluated thunks: slicing must not call them.
+    let thunks: Vec<Box<dyn Fn() -> i64>> = vec![
+        Box::new(|| panic!("forced")),
+        Box::new(|| 2),
+        Box::new(|| panic!("forced")),
+    ];
+    let middle = sublist(1, 1, &thunks);
+    assert_eq!(middle.len(), 1);
+    assert_eq!(middle[0](), 2);
+}
@@ -0,0 +1,62 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::diagnostic::Diagnostic;
+use nixrs::lex::Span;
+use nixrs::number::{div, modulo, to_float, to_int, ArithmeticError};
+
+use common::lex;
+
+#[test]
+fn ints() {
+    assert_eq!(to_int(" 42 "), Ok(42));
+    assert_eq!(to_int("\t-7\n"), Ok(-7));
+    assert_eq!(to_int("0"), Ok(0));
+
+    for s in &["x", "", "4 2", "+1", "1.0", "0x10", "-"] {
+        assert!(to_int(s).is_err(), "{:?} converted to an int", s);
+    }
+    assert_eq!(to_int("x").unwrap_err().to_string(), "could not convert \"x\" to an integer");
+    assert_eq!(to_int("007").unwrap_err().to_string(),
+               "could not convert \"007\" to an integer: leading zeros are ambiguous");
+    assert_eq!(to_int("99999999999999999999").unwrap_err().reason,
+               Some("it doesn't fit in 64 bits"));
+}
+
+#[test]
+fn floats() {
+    assert_eq!(to_float("3.25"), Ok(3.25));
+    assert_eq!(to_float(" -2 "), Ok(-2.0));
+    assert_eq!(to_float("1.5e3"), Ok(1500.0));
+
+    for s in &["x", "inf", "NaN", "+1", ".5", "1.", "1e", "1e+", "-", ""] {
+        assert!(to_float(s).is_err(), "{:?} converted to a float", s);
+    }
+    assert_eq!(to_float("x").unwrap_err().to_string(), "could not convert \"x\" to a float");
+}
+
+#[test]
+fn integer_division_truncates() {
+    assert_eq!(div(7, 2), Ok(3));
+    assert_eq!(div(-7, 2), Ok(-3));
+    assert_eq!(div(7, -2), Ok(-3));
+    assert_eq!(modulo(7, 3), Ok(1));
+    assert_eq!(modulo(-7, 3), Ok(-1));
+    assert_eq!(modulo(7, -3), Ok(1));
+    assert_eq!(div(i64::MIN, -1), Err(ArithmeticError::Overflow));
+    assert_eq!(modulo(i64::MIN, -1), Err(ArithmeticError::Overflow));
+}
+
+#[test]
+fn division_by_zero_is_reported_at_the_operation() {
+    assert_eq!(div(1, 0), Err(ArithmeticError::DivisionByZero));
+    assert_eq!(modulo(1, 0), Err(ArithmeticError::DivisionByZero));
+
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "test.nix", "1 + builtins.div 1 0");
+    let call = Span { start: tokens[2].span.start, ..tokens[tokens.len() - 1].span };
+    let diagnostic = Diagnostic::error(call, div(1, 0).unwrap_err().to_string());
+    assert_eq!(diagnostic.to_string(), "test.nix:1:5: error: division by zero");
+}
@@ -0,0 +1,131 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::ast::{BinOp, BINARY_OPERATORS};
+use nixrs::context::EvalContext;
+use nixrs::lex::TokenKind;
+
+use common::lex;
+
+/// Every `TokenKind`, found by following `next_kind` from the first one.
+fn all_token_kinds() -> Vec<TokenKind> {
+    let mut kinds = vec![TokenKind::Comment];
+    while let Some(next) = next_kind(*kinds.last().unwrap()) {
+        kinds.push(next);
+    }
+    kinds
+}
+
+/// The token kind after `kind` in declaration order. There's no wildcard arm, so adding a
+/// `TokenKind` variant won't compile until it's added here too, and therefore to `all_token_kinds`.
+fn next_kind(kind: TokenKind) -> Option<TokenKind> {
+    use nixrs::lex::StringStyle::{Indent, Normal};
+    use nixrs::lex::TokenKind::*;
+
+    Some(match kind {
+        Comment => Identifier,
+        Identifier => Integer,
+        Integer => Float,
+        Float => Path,
+        Path => Uri,
+        Uri => StrPart(Normal),
+        StrPart(Normal) => StrPart(Indent),
+        StrPart(Indent) => Quote(Normal),
+        Quote(Normal) => Quote(Indent),
+        Quote(Indent) => DollarBraceL,
+        DollarBraceL => Mult,
+        Mult => Minus,
+        Minus => Plus,
+        Plus => Divide,
+        Divide => Less,
+        Less => Greater,
+        Greater => LessEq,
+        LessEq => GreaterEq,
+        GreaterEq => Assign,
+        Assign => Equals,
+        Equals => NotEquals,
+        NotEquals => And,
+        And => Or,
+        Or => Implies,
+        Implies => Not,
+        Not => Update,
+        Update => Concat,
+        Concat => At,
+        At => Comma,
+        Comma => Dot,
+        Dot => Ellipsis,
+        Ellipsis => Question,
+        Question => Colon,
+        Colon => Semicolon,
+        Semicolon => ParenL,
+        ParenL => ParenR,
+        ParenR => BracketL,
+        BracketL => BracketR,
+        BracketR => BraceL,
+        BraceL => BraceR,
+        BraceR => KeywordIf,
+        KeywordIf => KeywordThen,
+        KeywordThen => KeywordElse,
+        KeywordElse => KeywordAssert,
+        KeywordAssert => KeywordWith,
+        KeywordWith => KeywordLet,
+        KeywordLet => KeywordIn,
+        KeywordIn => KeywordRec,
+        KeywordRec => KeywordInherit,
+        KeywordInherit => KeywordOr,
+        KeywordOr => return None,
+    })
+}
+
+/// Every token kind that can appear as a binary operator between two expressions: the operators,
+/// minus the prefix `!` and `?`, which takes an attribute path rather than an expression.
+fn binary_tokens() -> Vec<TokenKind> {
+    all_token_kinds().into_iter()
+        .filter(|&kind| kind.is_operator() && kind != TokenKind::Not && kind != TokenKind::Question)
+        .collect()
+}
+
+#[test]
+fn every_binary_token_has_one_entry() {
+    let binary_tokens = binary_tokens();
+    assert_eq!(BINARY_OPERATORS.len(), binary_tokens.len());
+
+    for &kind in &binary_tokens {
+        let entries = BINARY_OPERATORS.iter().filter(|info| info.token == kind).count();
+        assert_eq!(entries, 1, "{:?} has {} operator table entries", kind, entries);
+    }
+
+    for info in BINARY_OPERATORS.iter() {
+        let ops = BINARY_OPERATORS.iter().filter(|other| other.op == info.op).count();
+        assert_eq!(ops, 1, "{:?} has {} operator table entries", info.op, ops);
+    }
+}
+
+#[test]
+fn source_text_lexes_to_the_operator_token() {
+    let ctx = EvalContext::new();
+
+    for info in BINARY_OPERATORS.iter() {
+        let tokens = lex(&ctx, "<operator>", info.source);
+        assert_eq!(tokens.len(), 1, "{:?} lexed to {:?}", info.source, tokens);
+        assert_eq!(tokens[0].kind, info.token);
+        assert_eq!(BinOp::from_token(tokens[0].kind), Some(info.op));
+        assert_eq!(info.op.as_str(), info.source);
+    }
+}
+
+#[test]
+fn non_operators() {
+    assert_eq!(BinOp::from_token(TokenKind::Not), None);
+    assert_eq!(BinOp::from_token(TokenKind::Question), None);
+    assert_eq!(BinOp::from_token(TokenKind::Identifier), None);
+}
+
+#[test]
+fn precedence_order() {
+    assert!(BinOp::Mult.precedence() > BinOp::Add.precedence());
+    assert!(BinOp::Concat.precedence() > BinOp::Mult.precedence());
+    assert!(BinOp::And.precedence() > BinOp::Or.precedence());
+    assert!(BinOp::Or.precedence() > BinOp::Implies.precedence());
+}
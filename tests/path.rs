@@ -0,0 +1,81 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::path::{base_name_of, dir_of, normalize_path};
+
+#[test]
+fn normalize() {
+    let cases = [
+        ("./a/./b/../c", "a/c"),
+        ("/a/./b/../c", "/a/c"),
+        ("/a//b///c/", "/a/b/c"),
+        ("/", "/"),
+        ("//", "/"),
+        ("/..", "/"),
+        ("/a/../../b", "/b"),
+        ("../a/../../b", "../../b"),
+        ("a/..", "."),
+        (".", "."),
+    ];
+
+    for &(path, expected) in &cases {
+        assert_eq!(normalize_path(path), expected, "normalizing {:?}", path);
+    }
+}
+
+#[test]
+fn resolve_relative_paths() {
+    let ctx = EvalContext::new();
+    assert_eq!(ctx.resolve_path("./a/./b/../c", "/src/project"), Some("/src/project/a/c".into()));
+    assert_eq!(ctx.resolve_path("../lib.nix", "/src/project"), Some("/src/lib.nix".into()));
+    assert_eq!(ctx.resolve_path("/etc//nixos/", "/src/project"), Some("/etc/nixos".into()));
+}
+
+#[test]
+fn resolve_home_paths() {
+    let mut ctx = EvalContext::new();
+
+    ctx.set_home_dir(Some("/home/alice/"));
+    assert_eq!(ctx.resolve_path("~/x", "/src"), Some("/home/alice/x".into()));
+    assert_eq!(ctx.resolve_path("~/a/../b/", "/src"), Some("/home/alice/b".into()));
+
+    ctx.set_home_dir(None::<String>);
+    assert_eq!(ctx.resolve_path("~/x", "/src"), None);
+}
+
+#[test]
+fn base_names() {
+    let cases = [
+        ("/a/b/c", "c"),
+        ("/a/b/", "b"),
+        ("a/b", "b"),
+        ("foo.nix", "foo.nix"),
+        ("/", ""),
+        ("", ""),
+    ];
+
+    for &(path, expected) in &cases {
+        assert_eq!(base_name_of(path), expected, "baseNameOf {:?}", path);
+    }
+}
+
+#[test]
+fn dir_names() {
+    // `./a/b` as a path value is resolved before `dirOf` sees it.
+    let ctx = EvalContext::new();
+    let path = ctx.resolve_path("./a/b", "/src").unwrap();
+    assert_eq!(dir_of(&path), "/src/a");
+
+    let cases = [
+        ("/a/b/c", "/a/b"),
+        ("/a/b/", "/a/b"),
+        ("/a", "/"),
+        ("/", "/"),
+        ("a/b", "a"),
+        ("foo.nix", "."),
+    ];
+
+    for &(path, expected) in &cases {
+        assert_eq!(dir_of(path), expected, "dirOf {:?}", path);
+    }
+}
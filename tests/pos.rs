@@ -0,0 +1,176 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::{Pos, Span};
+use nixrs::source::LineIndex;
+use nixrs::symbol::Symbol;
+
+use common::lex;
+
+#[test]
+fn pos_round_trips_through_display() {
+    let pos: Pos = "3:7".parse().unwrap();
+    assert_eq!(pos, Pos { line: 3, column: 7, offset: 0 });
+    assert_eq!(pos.to_string(), "3:7");
+}
+
+#[test]
+fn span_round_trips_through_display() {
+    let filename = Symbol::new("test.nix");
+    let span = Span::parse(filename, "1:1-2:15").unwrap();
+    assert_eq!(span, Span {
+        filename,
+        start: Pos { line: 1, column: 1, offset: 0 },
+        end: Pos { line: 2, column: 15, offset: 0 },
+    });
+    assert_eq!(span.to_string(), "1:1-2:15");
+}
+
+#[test]
+fn span_debug_includes_filename() {
+    let span = Span::parse(Symbol::new("dir/test.nix"), "1:1-1:2").unwrap();
+    assert_eq!(format!("{:?}", span), "dir/test.nix:1:1-1:2");
+    assert_eq!(format!("{:?}", Some(span)), "Some(dir/test.nix:1:1-1:2)");
+
+    // Spans that print the same compare equal, even if only one knows its byte offsets.
+    let ctx = EvalContext::new();
+    let lexed = lex(&ctx, "dir/test.nix", "x")[0].span;
+    assert_eq!(format!("{:?}", lexed), format!("{:?}", span));
+    assert_eq!(lexed, span);
+}
+
+#[test]
+fn invalid_positions() {
+    for s in &["", "3", "3:", ":7", "3:7:1", "a:b", "0:1", "1:0", "-1:1"] {
+        assert!(s.parse::<Pos>().is_err(), "{:?} parsed as a Pos", s);
+    }
+
+    let filename = Symbol::new("test.nix");
+    for s in &["", "1:1", "1:1-", "1:1-2", "1:1-2:2-3:3"] {
+        assert!(Span::parse(filename, s).is_err(), "{:?} parsed as a Span", s);
+    }
+}
+
+fn pos(line: usize, column: usize) -> Pos {
+    Pos { line, column, offset: 0 }
+}
+
+fn span(filename: &str, start: Pos, end: Pos) -> Span {
+    Span { filename: Symbol::new(filename), start, end }
+}
+
+#[test]
+fn pos_ordering() {
+    assert!(pos(1, 10) < pos(2, 1));
+    assert!(pos(2, 1) < pos(2, 2));
+    assert!(pos(3, 7) == pos(3, 7));
+
+    let mut positions = vec![pos(2, 5), pos(1, 9), pos(2, 1), pos(1, 1)];
+    positions.sort();
+    assert_eq!(positions, [pos(1, 1), pos(1, 9), pos(2, 1), pos(2, 5)]);
+}
+
+#[test]
+fn span_ordering() {
+    let a1 = span("a.nix", pos(1, 1), pos(1, 5));
+    let a2 = span("a.nix", pos(1, 1), pos(1, 9));
+    let a3 = span("a.nix", pos(2, 1), pos(2, 2));
+    let b1 = span("b.nix", pos(1, 1), pos(1, 2));
+
+    let mut spans = vec![b1, a3, a2, a1];
+    spans.sort();
+    assert_eq!(spans, [a1, a2, a3, b1]);
+}
+
+#[test]
+fn span_contains() {
+    let s = span("test.nix", pos(1, 5), pos(2, 3));
+
+    assert!(!s.contains(pos(1, 4)));
+    assert!(s.contains(pos(1, 5)));  // The start is inside.
+    assert!(s.contains(pos(1, 80)));
+    assert!(s.contains(pos(2, 2)));
+    assert!(!s.contains(pos(2, 3))); // The end is outside.
+
+    let empty = span("test.nix", pos(1, 5), pos(1, 5));
+    assert!(!empty.contains(pos(1, 5)));
+}
+
+#[test]
+fn lexed_span_contains_parsed_cursor() {
+    // A cursor given as `line:column` has no byte offset, but still hits the first and last chars
+    // of a node lexed at that position.
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "a.nix", "{ foo = 1; }");
+    let foo = tokens[1].span;
+
+    assert!(!foo.contains("1:2".parse().unwrap()));
+    assert!(foo.contains("1:3".parse().unwrap()));
+    assert!(foo.contains("1:5".parse().unwrap()));
+    assert!(!foo.contains("1:6".parse().unwrap()));
+    assert!(foo.start <= "1:3".parse().unwrap() && foo.start >= "1:3".parse().unwrap());
+}
+
+#[test]
+fn span_overlaps() {
+    let s = span("test.nix", pos(1, 5), pos(1, 10));
+
+    assert!(s.overlaps(s));
+    assert!(s.overlaps(span("test.nix", pos(1, 1), pos(1, 6))));
+    assert!(s.overlaps(span("test.nix", pos(1, 9), pos(1, 20))));
+    assert!(s.overlaps(span("test.nix", pos(1, 6), pos(1, 7))));
+
+    // Touching spans share no chars.
+    assert!(!s.overlaps(span("test.nix", pos(1, 1), pos(1, 5))));
+    assert!(!s.overlaps(span("test.nix", pos(1, 10), pos(1, 12))));
+
+    assert!(!s.overlaps(span("other.nix", pos(1, 5), pos(1, 10))));
+
+    // Empty spans share no chars with anything, even when they're inside the other span.
+    let empty = span("test.nix", pos(1, 6), pos(1, 6));
+    assert!(!s.overlaps(empty));
+    assert!(!empty.overlaps(s));
+    assert!(!empty.overlaps(empty));
+}
+
+#[test]
+fn columns_count_chars_after_multi_byte_characters() {
+    let ctx = EvalContext::new();
+    let source = "\"🎉\" x\n\"e\u{301}\" y";
+    let tokens = lex(&ctx, "test.nix", source);
+
+    // `🎉` is one char but four bytes.
+    let x = tokens[3];
+    assert_eq!(x.source, "x");
+    assert_eq!(x.span.start, Pos { line: 1, column: 5, offset: 7 });
+    assert_eq!(x.span.start.offset, 7);
+
+    // A combining accent is a char of its own, so it takes a column.
+    let y = tokens[7];
+    assert_eq!(y.source, "y");
+    assert_eq!(y.span.start, Pos { line: 2, column: 6, offset: 15 });
+    assert_eq!(y.span.start.offset, 15);
+
+    // The line index agrees with the lexer in both directions.
+    let index = LineIndex::new(source);
+    for token in &[x, y] {
+        let pos = index.pos(source, token.span.start.offset).unwrap();
+        assert_eq!((pos, pos.offset), (token.span.start, token.span.start.offset));
+        assert_eq!(index.offset(source, token.span.start), Some(token.span.start.offset));
+    }
+}
+
+#[test]
+fn parsed_spans_equal_lexed_spans() {
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "a.nix", "{ foo = 1; }");
+    let foo = tokens[1];
+    assert_eq!(foo.source, "foo");
+
+    // The lexed span knows its byte offsets and the parsed one doesn't, but they're still equal.
+    assert_eq!(foo.span, Span::parse(Symbol::new("a.nix"), "1:3-1:6").unwrap());
+    assert_eq!(foo.span.start, "1:3".parse().unwrap());
+    assert_ne!(foo.span.start.offset, 0);
+}
@@ -0,0 +1,29 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::{self, Lexer, Token, TokenKind};
+
+#[test]
+fn slice_of_operator_token() {
+    let ctx = EvalContext::new();
+    let source = "foo + bar";
+    let mut lexer = Lexer::new(&ctx, "test.nix", source);
+    let tokens: Vec<Token> = lexer.by_ref().map(Result::unwrap).collect();
+
+    let plus = tokens.iter().find(|t| t.kind == TokenKind::Plus).unwrap();
+    assert_eq!((plus.span.start.offset, plus.span.end.offset), (4, 5));
+    assert_eq!(lexer.source_slice(plus.span), "+");
+    assert_eq!(lex::source_slice(source, plus.span), "+");
+}
+
+#[test]
+fn slices_match_token_source() {
+    let ctx = EvalContext::new();
+    let source = "let s = \"é ${x}\"; in /* 🎉 */ s";
+    let mut lexer = Lexer::new(&ctx, "test.nix", source);
+    let tokens: Vec<Token> = lexer.by_ref().map(Result::unwrap).collect();
+
+    for token in &tokens {
+        assert_eq!(lexer.source_slice(token.span), token.source);
+    }
+}
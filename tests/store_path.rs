@@ -0,0 +1,49 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::path::StorePathError;
+
+const HASH: &str = "0c0i9ffzh8mb0z7nlxgqmf5dnmwiyyd0";
+
+#[test]
+fn set_store_dir() {
+    let mut ctx = EvalContext::new();
+    ctx.set_store_dir("/nix/store");
+    assert_eq!(ctx.store_dir(), "/nix/store");
+
+    ctx.set_store_dir("/tmp/store");
+    assert_eq!(ctx.store_dir(), "/tmp/store");
+}
+
+#[test]
+fn valid_store_paths() {
+    let mut ctx = EvalContext::new();
+    ctx.set_store_dir("/nix/store");
+
+    let store_path = format!("/nix/store/{}-hello-2.10", HASH);
+    assert_eq!(ctx.to_store_path(&store_path), Ok(store_path.clone()));
+    assert_eq!(ctx.to_store_path(&format!("{}/bin/hello", store_path)), Ok(store_path.clone()));
+    assert_eq!(ctx.to_store_path(&format!("/nix//store/./{}-hello-2.10/", HASH)),
+               Ok(store_path));
+}
+
+#[test]
+fn invalid_store_paths() {
+    let mut ctx = EvalContext::new();
+    ctx.set_store_dir("/nix/store");
+
+    let wrong_prefix = format!("/gnu/store/{}-hello", HASH);
+    assert_eq!(ctx.to_store_path(&wrong_prefix), Err(StorePathError::NotInStore(wrong_prefix)));
+    assert_eq!(ctx.to_store_path("/nix/store"),
+               Err(StorePathError::NotInStore("/nix/store".into())));
+    assert_eq!(ctx.to_store_path("/nix/storefoo"),
+               Err(StorePathError::NotInStore("/nix/storefoo".into())));
+
+    let bad_hash = "/nix/store/eeee-hello";
+    assert_eq!(ctx.to_store_path(bad_hash), Err(StorePathError::InvalidHash(bad_hash.into())));
+
+    let bad_name = format!("/nix/store/{}-.hidden", HASH);
+    assert_eq!(ctx.to_store_path(&bad_name), Err(StorePathError::InvalidName(bad_name.clone())));
+    assert_eq!(StorePathError::InvalidName(bad_name.clone()).to_string(),
+               format!("store path '{}' has an invalid name", bad_name));
+}
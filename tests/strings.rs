@@ -0,0 +1,90 @@
+extern crate nixrs;
+
+use nixrs::strings::{concat_map_strings_sep, has_prefix, has_suffix, optional_string};
+use nixrs::strings::{remove_prefix, remove_suffix, replace_strings, string_length, substring};
+use nixrs::strings::SubstringError;
+
+#[test]
+fn replace_strings_is_left_to_right_and_non_overlapping() {
+    assert_eq!(replace_strings("Hello world", &[("o", "a")]), "Hella warld");
+    assert_eq!(replace_strings("aaaa", &[("aa", "b")]), "bb");
+    assert_eq!(replace_strings("aaa", &[("aa", "b")]), "ba");
+
+    // Replacements aren't rescanned, so they can't cascade into each other.
+    assert_eq!(replace_strings("ab", &[("a", "b"), ("b", "c")]), "bc");
+    assert_eq!(replace_strings("abc", &[("b", "ab")]), "aabc");
+}
+
+#[test]
+fn replace_strings_prefers_earlier_patterns() {
+    assert_eq!(replace_strings("foobar", &[("foo", "1"), ("foobar", "2")]), "1bar");
+    assert_eq!(replace_strings("foobar", &[("foobar", "2"), ("foo", "1")]), "2");
+    assert_eq!(replace_strings("abc", &[("bc", "X"), ("ab", "Y")]), "Yc");
+}
+
+#[test]
+fn replace_strings_empty_pattern_inserts_between_chars() {
+    assert_eq!(replace_strings("abc", &[("", "-")]), "-a-b-c-");
+    assert_eq!(replace_strings("", &[("", "-")]), "-");
+    assert_eq!(replace_strings("ab", &[("a", "X"), ("", "-")]), "X-b-");
+    assert_eq!(replace_strings("λé", &[("", ".")]), ".λ.é.");
+}
+
+#[test]
+fn replace_strings_without_matches() {
+    assert_eq!(replace_strings("abc", &[]), "abc");
+    assert_eq!(replace_strings("abc", &[("x", "y")]), "abc");
+    assert_eq!(replace_strings("", &[("a", "b")]), "");
+}
+
+#[test]
+fn string_length_counts_bytes() {
+    assert_eq!(string_length(""), 0);
+    assert_eq!(string_length("abc"), 3);
+    assert_eq!(string_length("é"), 2);
+    assert_eq!(string_length("λx"), 3);
+}
+
+#[test]
+fn substring_indexes_bytes() {
+    assert_eq!(substring(1, 2, "hello"), Ok("el"));
+    assert_eq!(substring(0, 2, "éa"), Ok("é"));
+    assert_eq!(substring(2, 1, "éa"), Ok("a"));
+    assert_eq!(substring(1, -1, "hello"), Ok("ello"));
+    assert_eq!(substring(3, 10, "hello"), Ok("lo"));
+    assert_eq!(substring(10, 1, "hello"), Ok(""));
+    assert_eq!(substring(0, 0, "hello"), Ok(""));
+}
+
+#[test]
+fn substring_errors() {
+    assert_eq!(substring(-1, 1, "hello"), Err(SubstringError::NegativeStart(-1)));
+    assert_eq!(substring(1, 1, "é"), Err(SubstringError::SplitsChar(1)));
+    assert_eq!(substring(0, 1, "éa"), Err(SubstringError::SplitsChar(1)));
+    assert_eq!(
+        substring(0, 1, "é").unwrap_err().to_string(),
+        "'substring' would split a UTF-8 character at byte 1"
+    );
+}
+
+#[test]
+fn prefixes_and_suffixes() {
+    assert!(has_prefix("ab", "abc"));
+    assert!(!has_prefix("bc", "abc"));
+    assert!(has_prefix("", "abc"));
+    assert!(has_suffix(".nix", "x.nix"));
+    assert!(!has_suffix(".nix", "nix"));
+    assert_eq!(remove_prefix("ab", "abc"), "c");
+    assert_eq!(remove_prefix("x", "abc"), "abc");
+    assert_eq!(remove_suffix(".nix", "x.nix"), "x");
+    assert_eq!(remove_suffix(".nix", "x.txt"), "x.txt");
+}
+
+#[test]
+fn templating_helpers() {
+    assert_eq!(optional_string(false, "x"), "");
+    assert_eq!(optional_string(true, "x"), "x");
+    assert_eq!(concat_map_strings_sep(", ", |n: i64| n.to_string(), vec![1, 2, 3]), "1, 2, 3");
+    assert_eq!(concat_map_strings_sep("-", |s: &str| s.to_uppercase(), vec!["a"]), "A");
+    assert_eq!(concat_map_strings_sep("-", |s: &str| s.to_owned(), Vec::new()), "");
+}
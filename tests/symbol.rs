@@ -0,0 +1,28 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::TokenKind;
+use nixrs::symbol::Symbol;
+
+use common::lex;
+
+#[test]
+fn symbols_debug_as_their_string() {
+    let sym = Symbol::new("foo");
+    assert_eq!(format!("{:?}", sym), "\"foo\"");
+    assert_eq!(format!("{}", sym), "foo");
+    assert_eq!(Symbol::new(String::from("foo")), sym);
+}
+
+#[test]
+fn identifier_token_debug_output() {
+    let ctx = EvalContext::new();
+    let token = lex(&ctx, "debug.nix", "hello").remove(0);
+    assert_eq!(token.kind, TokenKind::Identifier);
+
+    // The filename symbol is shown as its string, not as an interner index.
+    assert_eq!(format!("{:?}", token),
+        "Token { kind: Identifier, span: debug.nix:1:1-1:6, source: \"hello\" }");
+}
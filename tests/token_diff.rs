@@ -0,0 +1,63 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::lex;
+
+use common::lex;
+
+#[test]
+fn identical_streams() {
+    let ctx = EvalContext::new();
+    let left = lex(&ctx, "test.nix", "a + b");
+    let right = lex(&ctx, "test.nix", "a + b");
+    assert_eq!(lex::diff_tokens(&left, &right), None);
+}
+
+#[test]
+fn differing_token() {
+    let ctx = EvalContext::new();
+    let left = lex(&ctx, "test.nix", "[ a + b ]");
+    let right = lex(&ctx, "test.nix", "[ a - b ]");
+
+    let diff = lex::diff_tokens(&left, &right).expect("streams should differ");
+    assert_eq!(diff.index, 2);
+    assert_eq!(diff.left, Some(left[2]));
+    assert_eq!(diff.right, Some(right[2]));
+    assert_eq!(diff.to_string(), "token streams differ at index 2:
+  left:  [1:5-1:6] (Plus) \"+\"
+  right: [1:5-1:6] (Minus) \"-\"");
+}
+
+#[test]
+fn differing_length() {
+    let ctx = EvalContext::new();
+    let left = lex(&ctx, "test.nix", "f x");
+    let right = lex(&ctx, "test.nix", "f");
+
+    let diff = lex::diff_tokens(&left, &right).expect("streams should differ");
+    assert_eq!(diff.index, 1);
+    assert_eq!(diff.right, None);
+    assert_eq!(diff.to_string(), "token streams differ at index 1:
+  left:  [1:3-1:4] (Identifier) \"x\"
+  right: <end of tokens>");
+}
+
+#[test]
+fn differing_offsets() {
+    let ctx = EvalContext::new();
+    let left = lex(&ctx, "test.nix", "a + b");
+    let mut right = left.clone();
+
+    // A stale splice could leave the right positions with the wrong byte offsets.
+    right[1].span.start.offset += 1;
+    right[1].span.end.offset += 1;
+    assert_eq!(left[1], right[1]);
+
+    let diff = lex::diff_tokens(&left, &right).expect("streams should differ");
+    assert_eq!(diff.index, 1);
+    assert_eq!(diff.to_string(), "token streams differ at index 1:
+  left:  [1:3-1:4] (Plus) \"+\" at bytes 2..3
+  right: [1:3-1:4] (Plus) \"+\" at bytes 3..4");
+}
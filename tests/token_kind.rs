@@ -0,0 +1,61 @@
+extern crate nixrs;
+
+mod common;
+
+use nixrs::context::EvalContext;
+use nixrs::lex::{StringStyle, TokenKind};
+
+use common::lex;
+
+#[test]
+fn classification() {
+    // (kind, trivia, keyword, operator, literal, delimiter)
+    let cases = [
+        (TokenKind::Comment, true, false, false, false, false),
+        (TokenKind::KeywordInherit, false, true, false, false, false),
+        (TokenKind::KeywordOr, false, true, false, false, false),
+        (TokenKind::Update, false, false, true, false, false),
+        (TokenKind::Question, false, false, true, false, false),
+        (TokenKind::Integer, false, false, false, true, false),
+        (TokenKind::StrPart(StringStyle::Indent), false, false, false, true, false),
+        (TokenKind::DollarBraceL, false, false, false, false, true),
+        (TokenKind::Quote(StringStyle::Normal), false, false, false, false, true),
+        (TokenKind::Identifier, false, false, false, false, false),
+        (TokenKind::Semicolon, false, false, false, false, false),
+    ];
+
+    for &(kind, trivia, keyword, operator, literal, delimiter) in &cases {
+        assert_eq!(kind.is_trivia(), trivia, "{:?}.is_trivia()", kind);
+        assert_eq!(kind.is_keyword(), keyword, "{:?}.is_keyword()", kind);
+        assert_eq!(kind.is_operator(), operator, "{:?}.is_operator()", kind);
+        assert_eq!(kind.is_literal(), literal, "{:?}.is_literal()", kind);
+        assert_eq!(kind.is_delimiter(), delimiter, "{:?}.is_delimiter()", kind);
+    }
+}
+
+#[test]
+fn token_accessors() {
+    let ctx = EvalContext::new();
+    let tokens = lex(&ctx, "test.nix", "# hi\nlet");
+
+    assert!(tokens[0].is_trivia());
+    assert_eq!(tokens[1].kind(), TokenKind::KeywordLet);
+    assert_eq!(tokens[1].span().to_string(), "2:1-2:4");
+    assert!(tokens.iter().filter(|t| !t.is_trivia()).all(|t| t.kind().is_keyword()));
+}
+
+#[test]
+fn number_literals_keep_their_source() {
+    let ctx = EvalContext::new();
+    let tokens: Vec<_> = lex(&ctx, "test.nix", "1.50 007 2.5e3 .5")
+        .into_iter()
+        .map(|t| (t.kind, t.source))
+        .collect();
+
+    assert_eq!(tokens, [
+        (TokenKind::Float, "1.50"),
+        (TokenKind::Integer, "007"),
+        (TokenKind::Float, "2.5e3"),
+        (TokenKind::Float, ".5"),
+    ]);
+}
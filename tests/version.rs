@@ -0,0 +1,32 @@
+extern crate nixrs;
+
+use std::cmp::Ordering;
+
+use nixrs::version::{compare_versions, parse_drv_name, split_version};
+
+#[test]
+fn drv_names() {
+    assert_eq!(parse_drv_name("hello-2.10"), ("hello", "2.10"));
+    assert_eq!(parse_drv_name("hello"), ("hello", ""));
+    assert_eq!(parse_drv_name("foo-bar-baz-1.0-rc1"), ("foo-bar-baz", "1.0-rc1"));
+    assert_eq!(parse_drv_name("nix-unstable-2023"), ("nix-unstable", "2023"));
+    assert_eq!(parse_drv_name("trailing-"), ("trailing-", ""));
+}
+
+#[test]
+fn version_components() {
+    assert_eq!(split_version("1.2.3"), ["1", "2", "3"]);
+    assert_eq!(split_version("2.3pre1"), ["2", "3", "pre", "1"]);
+    assert_eq!(split_version("1.0-rc1..x"), ["1", "0", "rc", "1", "x"]);
+    assert!(split_version("").is_empty());
+}
+
+#[test]
+fn version_ordering() {
+    let ascending = ["1.0pre1", "1.0", "1.0a", "1.0.1", "1.1", "1.10", "2.3a", "2.3.1", "10"];
+    for pair in ascending.windows(2) {
+        assert_eq!(compare_versions(pair[0], pair[1]), Ordering::Less, "{:?}", pair);
+        assert_eq!(compare_versions(pair[1], pair[0]), Ordering::Greater, "{:?}", pair);
+    }
+    assert_eq!(compare_versions("1.0", "1-0"), Ordering::Equal);
+}
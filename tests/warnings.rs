@@ -0,0 +1,35 @@
+extern crate nixrs;
+
+use nixrs::context::EvalContext;
+use nixrs::diagnostic::{Diagnostic, Severity};
+use nixrs::lex::Span;
+use nixrs::symbol::Symbol;
+
+fn warning(message: &str) -> Diagnostic {
+    let span = Span::parse(Symbol::new("test.nix"), "1:1-1:5").unwrap();
+    Diagnostic::warning(span, message)
+}
+
+#[test]
+fn warnings_are_recorded() {
+    let ctx = EvalContext::new();
+    assert_eq!(ctx.warn(warning("first")), Ok(()));
+    assert_eq!(ctx.warn(warning("second")), Ok(()));
+
+    let warnings = ctx.take_warnings();
+    let messages: Vec<_> = warnings.iter().map(|w| &w.message[..]).collect();
+    assert_eq!(messages, ["first", "second"]);
+    assert!(warnings.iter().all(|w| w.severity == Severity::Warning));
+    assert!(ctx.take_warnings().is_empty());
+}
+
+#[test]
+fn warnings_as_errors() {
+    let mut ctx = EvalContext::new();
+    ctx.set_warnings_as_errors(true);
+
+    let error = ctx.warn(warning("x")).unwrap_err();
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(error.message, "x");
+    assert!(ctx.take_warnings().is_empty());
+}